@@ -49,6 +49,26 @@ impl AABB {
         }
     }
 
+    /// Total area of the six faces. Zero for an empty box.
+    #[must_use]
+    pub const fn surface_area(&self) -> f64 {
+        let (dx, dy, dz) = (self.x.size(), self.y.size(), self.z.size());
+        if dx < 0.0 || dy < 0.0 || dz < 0.0 {
+            return 0.0;
+        }
+        2.0 * (dx * dy + dy * dz + dz * dx)
+    }
+
+    /// Centre point of the box.
+    #[must_use]
+    pub const fn centroid(&self) -> Point3 {
+        Point3::new(
+            0.5 * (self.x.min + self.x.max),
+            0.5 * (self.y.min + self.y.max),
+            0.5 * (self.z.min + self.z.max),
+        )
+    }
+
     /// Adjusts [`AABB`] so that no side is narrower than some delta, padding it
     /// if necessary.
     const fn pad_to_minimum(x: Interval, y: Interval, z: Interval) -> Self {
@@ -61,7 +81,12 @@ impl AABB {
     }
 
     #[must_use]
-    pub fn hit(&self, ray: &Ray, mut ray_t: Interval) -> bool {
+    pub fn hit(&self, ray: &Ray, ray_t: Interval) -> bool { self.clip(ray, ray_t).is_some() }
+
+    /// The part of `ray_t` during which `ray` is inside the box, or `None` if
+    /// it never is.
+    #[must_use]
+    pub fn clip(&self, ray: &Ray, mut ray_t: Interval) -> Option<Interval> {
         for axis in [Axis::X, Axis::Y, Axis::Z] {
             let ax = self.get(axis);
             let adinv = ray.direction.get(axis).recip();
@@ -81,10 +106,10 @@ impl AABB {
             }
 
             if ray_t.max <= ray_t.min {
-                return false;
+                return None;
             }
         }
-        true
+        Some(ray_t)
     }
 }
 
@@ -0,0 +1,245 @@
+//! Keyframed transforms, for objects that move while the shutter is open.
+
+use std::sync::Arc;
+
+use crate::instance::hit_transformed;
+use crate::prelude::{AABB, HitRecord, Hittable, Interval, Quaternion, Ray, Transform, Vec3};
+
+/// Rotation samples per keyframe segment when bounding the motion.
+const ROTATION_STEPS: u32 = 32;
+
+/// An object's pose at one instant: scale, then rotate, then translate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe {
+    pub time: f64,
+    pub translation: Vec3,
+    pub rotation: Quaternion,
+    pub scale: Vec3,
+}
+
+impl Keyframe {
+    /// The identity pose at `time`.
+    #[inline]
+    #[must_use]
+    pub const fn at(time: f64) -> Self {
+        Self { time, translation: Vec3::ZERO, rotation: Quaternion::IDENTITY, scale: Vec3::ONE }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn with_translation(self, translation: Vec3) -> Self { Self { translation, ..self } }
+
+    #[inline]
+    #[must_use]
+    pub const fn with_rotation(self, rotation: Quaternion) -> Self { Self { rotation, ..self } }
+
+    #[inline]
+    #[must_use]
+    pub const fn with_scale(self, scale: Vec3) -> Self { Self { scale, ..self } }
+
+    #[must_use]
+    pub fn transform(&self) -> Transform {
+        Transform::translate(self.translation)
+            * Transform::from(self.rotation)
+            * Transform::scale(self.scale)
+    }
+
+    /// The pose a fraction `s` of the way to `next`.
+    fn lerp(&self, next: &Self, s: f64) -> Self {
+        Self {
+            time: (1.0 - s) * self.time + s * next.time,
+            translation: (1.0 - s) * self.translation + s * next.translation,
+            rotation: self.rotation.slerp(next.rotation, s),
+            scale: (1.0 - s) * self.scale + s * next.scale,
+        }
+    }
+}
+
+/// A transform that changes over time, interpolated between keyframes.
+///
+/// Translation and scale are interpolated linearly and rotation by
+/// [`Quaternion::slerp`]. Before the first keyframe and after the last, the
+/// pose holds still.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimatedTransform {
+    keyframes: Vec<Keyframe>,
+}
+
+impl AnimatedTransform {
+    /// Keyframes may be given in any order.
+    ///
+    /// # Panics
+    ///
+    /// If `keyframes` is empty.
+    #[must_use]
+    pub fn new(mut keyframes: Vec<Keyframe>) -> Self {
+        assert!(!keyframes.is_empty(), "an animation needs at least one keyframe");
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self { keyframes }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn keyframes(&self) -> &[Keyframe] { &self.keyframes }
+
+    /// The transform at `time`.
+    #[must_use]
+    pub fn at(&self, time: f64) -> Transform { self.pose(time).transform() }
+
+    fn pose(&self, time: f64) -> Keyframe {
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        let previous = next.checked_sub(1).and_then(|i| self.keyframes.get(i));
+        match (previous, self.keyframes.get(next)) {
+            (Some(a), Some(b)) => a.lerp(b, (time - a.time) / (b.time - a.time)),
+            (Some(k), None) | (None, Some(k)) => *k,
+            // `new` rules this out.
+            (None, None) => Keyframe::at(time),
+        }
+    }
+
+    /// A box enclosing `bbox` at every instant of the animation.
+    ///
+    /// Translation and scale move each point in a straight line between
+    /// keyframes, so the keyframe boxes cover them. Rotation is covered by
+    /// sampling each turning segment and padding the samples by twice the
+    /// furthest a point can swing between neighbouring samples.
+    #[must_use]
+    pub fn bounds(&self, bbox: AABB) -> AABB {
+        let mut total = AABB::EMPTY;
+        for k in &self.keyframes {
+            total = AABB::from((total, k.transform().bounds(bbox)));
+        }
+        for (a, b) in self.keyframes.iter().zip(self.keyframes.iter().skip(1)) {
+            let angle = a.rotation.angle_to(b.rotation);
+            if angle <= 0.0 {
+                continue;
+            }
+            let radius = reach(a.scale, bbox).max(reach(b.scale, bbox));
+            let padding = 2.0 * radius * angle / f64::from(ROTATION_STEPS);
+            for i in 0..=ROTATION_STEPS {
+                let sample = a.lerp(b, f64::from(i) / f64::from(ROTATION_STEPS));
+                let sample = sample.transform().bounds(bbox);
+                total = AABB::from((total, grow(sample, padding)));
+            }
+        }
+        total
+    }
+}
+
+/// The furthest any point of `bbox`, scaled by `scale`, lies from the origin.
+fn reach(scale: Vec3, bbox: AABB) -> f64 {
+    let axis = |s: f64, i: Interval| (s * i.min).abs().max((s * i.max).abs());
+    Vec3::new(axis(scale.x, bbox.x), axis(scale.y, bbox.y), axis(scale.z, bbox.z)).length()
+}
+
+fn grow(bbox: AABB, by: f64) -> AABB {
+    AABB::new(bbox.x.expand(2.0 * by), bbox.y.expand(2.0 * by), bbox.z.expand(2.0 * by))
+}
+
+// ---------------------------------------------------------------------------
+// Animated
+// ---------------------------------------------------------------------------
+
+/// An object that follows an [`AnimatedTransform`].
+///
+/// Each ray sees the pose at its own [`Ray::time`], so an object moving while
+/// the camera's shutter is open comes out motion-blurred.
+pub struct Animated {
+    object: Arc<dyn Hittable>,
+    animation: AnimatedTransform,
+    bbox: AABB,
+}
+
+impl Animated {
+    #[must_use]
+    pub fn new(object: Arc<dyn Hittable>, animation: AnimatedTransform) -> Self {
+        let bbox = animation.bounds(object.bounding_box());
+        Self { object, animation, bbox }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn animation(&self) -> &AnimatedTransform { &self.animation }
+}
+
+impl Hittable for Animated {
+    fn bounding_box(&self) -> AABB { self.bbox }
+
+    fn hit(&self, ray: &Ray, t: Interval) -> Option<HitRecord> {
+        hit_transformed(self.object.as_ref(), &self.animation.at(ray.time), ray, t)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Color3, Lambertian, Material, Point3, make_box, point3, vec3};
+
+    fn grey() -> Arc<dyn Material> { Arc::new(Lambertian::new(Color3::new(0.5, 0.5, 0.5))) }
+
+    #[test]
+    fn scenario_interpolated_pose() {
+        let animation = AnimatedTransform::new(vec![
+            Keyframe::at(1.0)
+                .with_translation(vec3(4, 0, 0))
+                .with_rotation(Quaternion::from_axis_angle(Vec3::Z, 90.0))
+                .with_scale(vec3(3, 3, 3)),
+            Keyframe::at(0.0),
+        ]);
+        let p = animation.at(0.5).point(Vec3::X);
+        // Halfway: scale 2, a 45° turn, then 2 along x.
+        let half = 0.5_f64.sqrt();
+        assert!((p - point3(2.0 + 2.0 * half, 2.0 * half, 0.0)).length() < 1e-9, "{p}");
+
+        // The pose holds outside the keyframes.
+        assert_eq!(animation.at(-1.0), Transform::IDENTITY);
+        assert_eq!(animation.at(2.0), animation.at(1.0));
+    }
+
+    #[test]
+    fn scenario_moving_object_is_hit_where_it_is_at_ray_time() {
+        let cube: Arc<dyn Hittable> = Arc::new(make_box(Point3::ZERO, Point3::ONE, &grey()));
+        let animation = AnimatedTransform::new(vec![
+            Keyframe::at(0.0),
+            Keyframe::at(1.0).with_translation(vec3(10, 0, 0)),
+        ]);
+        let moving = Animated::new(cube, animation);
+
+        let ray = |x: f64, time: f64| Ray::new(point3(x, 0.5, 5.0), -Vec3::Z, Some(time));
+        let t = Interval::new(0.001, f64::INFINITY);
+        assert!(moving.hit(&ray(0.5, 0.0), t).is_some());
+        assert!(moving.hit(&ray(0.5, 1.0), t).is_none());
+        assert!(moving.hit(&ray(5.5, 0.5), t).is_some());
+
+        let bbox = moving.bounding_box();
+        assert!(bbox.x.contains(0.0) && bbox.x.contains(11.0));
+    }
+
+    #[test]
+    fn scenario_bounds_cover_the_swept_arc() {
+        // A long thin bar along +x, swung a half turn about y. Halfway it
+        // points along −z, which neither keyframe box reaches.
+        let bar: Arc<dyn Hittable> =
+            Arc::new(make_box(point3(0.0, -0.1, -0.1), point3(4.0, 0.1, 0.1), &grey()));
+        let animation = AnimatedTransform::new(vec![
+            Keyframe::at(0.0),
+            Keyframe::at(1.0).with_rotation(Quaternion::from_axis_angle(Vec3::Y, 180.0)),
+        ]);
+        let swinging = Animated::new(bar, animation.clone());
+
+        let bbox = swinging.bounding_box();
+        for i in 0..=100 {
+            let pose = animation.at(f64::from(i) / 100.0);
+            let tip = pose.point(point3(4.0, 0.1, 0.1));
+            assert!(bbox.x.contains(tip.x) && bbox.z.contains(tip.z), "{tip} at step {i}");
+        }
+        assert!(bbox.z.min < -4.0);
+
+        let ray = Ray::new(point3(0.0, 0.0, -3.0), Vec3::X, Some(0.5));
+        assert!(swinging.hit(&ray, Interval::UNIVERSE).is_some());
+    }
+}
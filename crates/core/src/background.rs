@@ -0,0 +1,141 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::image::ImageError;
+use crate::prelude::{Color3, Image, ImageTexture, Texture as _, TransferFunction, Vec3};
+use crate::sphere::sphere_uv;
+use crate::texture::{Filter, Wrap};
+
+/// Radiance seen by rays that escape the scene.
+#[derive(Clone, Debug)]
+pub enum Background {
+    /// The same colour in every direction. Black for interiors lit only by
+    /// emitters.
+    Solid(Color3),
+    /// Linear blend on the direction's `y` component, from `bottom` straight
+    /// down to `top` straight up.
+    Gradient { bottom: Color3, top: Color3 },
+    /// An image-based environment, looked up by direction.
+    Environment(EnvironmentMap),
+}
+
+impl Background {
+    /// The books' sky: white straight down, blending to light blue overhead.
+    pub const SKY: Self = Self::Gradient { bottom: Color3::WHITE, top: Color3::new(0.5, 0.7, 1.0) };
+
+    /// Radiance arriving from `direction`, which need not be unit length.
+    #[must_use]
+    pub fn value(&self, direction: Vec3) -> Color3 {
+        match *self {
+            Self::Solid(c) => c,
+            Self::Gradient { bottom, top } => {
+                let a = 0.5 * (direction.unit().y + 1.0);
+                (1.0 - a) * bottom + a * top
+            }
+            Self::Environment(ref map) => map.value(direction),
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self { Self::SKY }
+}
+
+impl From<Color3> for Background {
+    fn from(c: Color3) -> Self { Self::Solid(c) }
+}
+
+impl From<EnvironmentMap> for Background {
+    fn from(map: EnvironmentMap) -> Self { Self::Environment(map) }
+}
+
+// ---------------------------------------------------------------------------
+// Equirectangular environment map
+// ---------------------------------------------------------------------------
+
+/// A latitude–longitude (equirectangular) environment image.
+///
+/// The top row looks straight up (+y) and the bottom row straight down;
+/// columns follow the same `u` as [`sphere_uv`], so the centre column looks
+/// along +x.
+#[derive(Clone, Debug)]
+pub struct EnvironmentMap {
+    /// Bilinearly filtered, wrapping around horizontally.
+    pub texture: ImageTexture,
+    /// Rotation about +y in degrees, turning the environment around the
+    /// scene.
+    pub rotation: f64,
+    /// Multiplier on the looked-up radiance.
+    pub intensity: f64,
+}
+
+impl EnvironmentMap {
+    #[inline]
+    #[must_use]
+    pub const fn new(image: Arc<Image>) -> Self {
+        let texture = ImageTexture {
+            image,
+            filter: Filter::Bilinear,
+            wrap_u: Wrap::Repeat,
+            wrap_v: Wrap::Clamp,
+        };
+        Self { texture, rotation: 0.0, intensity: 1.0 }
+    }
+
+    /// Loads a lat-long image in any format [`Image::open`] reads, usually
+    /// `.hdr` or `.pfm`. 8- and 16-bit files are treated as sRGB.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        Ok(Self::new(Arc::new(Image::open(path, TransferFunction::Srgb)?)))
+    }
+
+    /// Radiance arriving from `direction`.
+    #[must_use]
+    pub fn value(&self, direction: Vec3) -> Color3 {
+        let d = direction.unit();
+        if !d.x.is_finite() {
+            return Color3::BLACK;
+        }
+
+        let (u, v) = sphere_uv(d);
+        let u = (u + self.rotation / 360.0).rem_euclid(1.0);
+        self.intensity * self.texture.value(u, v, d)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{color, vec3};
+
+    #[test]
+    fn scenario_sky_matches_the_books() {
+        let sky = Background::default();
+        assert_eq!(sky.value(Vec3::Y), color(0.5, 0.7, 1.0));
+        assert_eq!(sky.value(Vec3::NEG_Y), Color3::WHITE);
+        assert_eq!(sky.value(vec3(1.0, 0.0, 0.0)), color(0.75, 0.85, 1.0));
+    }
+
+    #[test]
+    fn scenario_environment_lookup_by_direction() {
+        // Four columns spanning u = 0..1; axis directions land between them.
+        let mut image = Image::new(4, 1);
+        for (x, c) in (0..4).zip([Color3::RED, Color3::GREEN, Color3::BLUE, Color3::WHITE]) {
+            image.set(x, 0, c);
+        }
+        let map = EnvironmentMap::new(Arc::new(image));
+
+        // u = 0.5 (+x) falls between columns 1 and 2.
+        assert_eq!(map.value(Vec3::X), 0.5 * (Color3::GREEN + Color3::BLUE));
+        // u = 0.75 (−z) falls between columns 2 and 3.
+        assert_eq!(map.value(Vec3::NEG_Z), 0.5 * (Color3::BLUE + Color3::WHITE));
+        // u = 0 (−x) wraps between the last and first columns.
+        assert_eq!(map.value(Vec3::NEG_X), 0.5 * (Color3::WHITE + Color3::RED));
+
+        let turned = EnvironmentMap { rotation: 90.0, ..map };
+        assert_eq!(turned.value(Vec3::X), 0.5 * (Color3::BLUE + Color3::WHITE));
+    }
+}
@@ -0,0 +1,519 @@
+//! Flattened BVH built with a binned surface-area heuristic (SAH).
+
+use core::fmt;
+use std::sync::Arc;
+
+use rayon::prelude::*;
+
+use crate::prelude::{AABB, Axis, HitRecord, Hittable, Hittables, Interval, Ray, interval};
+
+/// Cost of one traversal step, relative to one primitive intersection.
+const TRAVERSAL_COST: f64 = 0.125;
+/// Cost of one primitive intersection.
+const INTERSECTION_COST: f64 = 1.0;
+/// The SAH builder only makes a leaf this big if splitting would cost more.
+const MAX_LEAF_SIZE: usize = 4;
+/// Traversal stack size. The builder never emits a deeper tree.
+const MAX_DEPTH: usize = 64;
+/// Subtrees over more primitives than this are built on separate rayon tasks.
+const PARALLEL_THRESHOLD: usize = 1024;
+
+/// How the builder chooses where to split a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitMethod {
+    /// The Book 2 split: sort by box minimum along the longest axis and cut
+    /// the list in half. Every leaf holds exactly one primitive.
+    Middle,
+    /// Binned SAH: bucket primitive centroids into `bins` slabs per axis and
+    /// take the cheapest slab boundary, or make a leaf if no split pays off.
+    Sah { bins: usize },
+}
+
+impl Default for SplitMethod {
+    fn default() -> Self { Self::Sah { bins: 16 } }
+}
+
+/// Shape and estimated quality of a built hierarchy.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BvhStats {
+    /// Interior and leaf nodes.
+    pub node_count: usize,
+    pub leaf_count: usize,
+    /// Depth of the deepest leaf; the root is at depth 0.
+    pub max_depth: usize,
+    /// Expected cost of tracing a random ray through the tree, in units of one
+    /// primitive intersection. Lower is better.
+    pub sah_cost: f64,
+}
+
+impl fmt::Display for BvhStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} nodes ({} leaves), depth {}, SAH cost {:.3}",
+            self.node_count, self.leaf_count, self.max_depth, self.sah_cost
+        )
+    }
+}
+
+/// One entry of the flattened tree.
+///
+/// Nodes are stored in depth-first order, so an interior node's first child
+/// is always the next node in the array and only the second needs an index.
+#[derive(Clone, Copy, Debug)]
+struct LinearNode {
+    bbox: AABB,
+    /// Leaf: index of the first primitive. Interior: index of the second child.
+    offset: u32,
+    /// Number of primitives in a leaf; zero marks an interior node.
+    count: u32,
+    /// Split axis of an interior node, used to visit the nearer child first.
+    axis: Axis,
+}
+
+/// A BVH stored as a flat array of nodes over primitives of type `T`.
+///
+/// `T` is usually `Arc<dyn Hittable>`, in which case the tree is itself
+/// [`Hittable`], but any primitive with a bounding box works; see
+/// [`Self::hit_with`].
+pub struct LinearBvh<T> {
+    nodes: Vec<LinearNode>,
+    /// Primitives reordered so that every leaf covers a contiguous run.
+    primitives: Vec<T>,
+    stats: BvhStats,
+}
+
+impl<T: Sync> LinearBvh<T> {
+    /// Builds a hierarchy over `primitives`, using `bounds` to get each
+    /// primitive's box.
+    #[must_use]
+    pub fn build(
+        primitives: Vec<T>,
+        method: SplitMethod,
+        bounds: impl Fn(&T) -> AABB + Sync,
+    ) -> Self {
+        assert!(u32::try_from(primitives.len()).is_ok(), "too many BVH primitives");
+
+        if primitives.is_empty() {
+            return Self { nodes: Vec::new(), primitives, stats: BvhStats::default() };
+        }
+
+        let mut items: Vec<_> = primitives
+            .par_iter()
+            .enumerate()
+            .map(|(index, prim)| {
+                let bbox = bounds(prim);
+                BuildItem { index, bbox, centroid: Centroid::of(bbox) }
+            })
+            .collect();
+
+        let method = match method {
+            SplitMethod::Sah { bins } => SplitMethod::Sah { bins: bins.max(2) },
+            SplitMethod::Middle => SplitMethod::Middle,
+        };
+        let root = build_node(&mut items, 0, 0, method);
+
+        let root_area = root.bbox().surface_area();
+        let mut nodes = Vec::new();
+        let mut stats = BvhStats::default();
+        flatten(root, &mut nodes, &mut stats, 0, if root_area > 0.0 { root_area } else { 1.0 });
+
+        // Move each primitive to the slot its leaf expects.
+        let mut slots: Vec<_> = primitives.into_iter().map(Some).collect();
+        let primitives = items
+            .iter()
+            .filter_map(|item| slots.get_mut(item.index).and_then(Option::take))
+            .collect();
+
+        Self { nodes, primitives, stats }
+    }
+}
+
+impl<T> LinearBvh<T> {
+    #[inline]
+    #[must_use]
+    pub const fn stats(&self) -> BvhStats { self.stats }
+
+    /// The primitives in leaf order.
+    #[inline]
+    #[must_use]
+    pub fn primitives(&self) -> &[T] { &self.primitives }
+
+    /// Bounding box of the whole tree. `AABB::EMPTY` if it has no primitives.
+    #[inline]
+    #[must_use]
+    pub fn bounding_box(&self) -> AABB { self.nodes.first().map_or(AABB::EMPTY, |node| node.bbox) }
+
+    /// Returns the closest hit in `t`, calling `hit_primitive` for each
+    /// primitive in every leaf the ray reaches.
+    ///
+    /// `hit_primitive` receives an interval already shrunk to the closest hit
+    /// so far, like the callee of [`Hittables`]'s fold.
+    pub fn hit_with<'a>(
+        &'a self,
+        ray: &Ray,
+        t: Interval,
+        mut hit_primitive: impl FnMut(&'a T, Interval) -> Option<HitRecord>,
+    ) -> Option<HitRecord> {
+        let mut stack = [0usize; MAX_DEPTH];
+        let mut top = 0;
+        let mut current = 0;
+        let mut closest = t.max;
+        let mut best = None;
+
+        while let Some(node) = self.nodes.get(current) {
+            if node.bbox.hit(ray, interval(t.min, closest)) {
+                if node.count == 0 {
+                    // Descend into the child on the ray's near side first so
+                    // that `closest` shrinks early and prunes the far one.
+                    let (first, second) = (current + 1, to_usize(node.offset));
+                    let (near, far) = if ray.direction.get(node.axis) < 0.0 {
+                        (second, first)
+                    } else {
+                        (first, second)
+                    };
+                    if let Some(slot) = stack.get_mut(top) {
+                        *slot = far;
+                        top += 1;
+                    }
+                    current = near;
+                    continue;
+                }
+
+                let first = to_usize(node.offset);
+                let leaf =
+                    self.primitives.get(first..first + to_usize(node.count)).unwrap_or_default();
+                for prim in leaf {
+                    if let Some(rec) = hit_primitive(prim, interval(t.min, closest)) {
+                        closest = rec.t;
+                        best = Some(rec);
+                    }
+                }
+            }
+
+            let Some(next) = top.checked_sub(1) else { break };
+            top = next;
+            current = stack.get(top).copied().unwrap_or(usize::MAX);
+        }
+
+        best
+    }
+}
+
+impl LinearBvh<Arc<dyn Hittable>> {
+    /// Builds a hierarchy over every object in `list`.
+    #[must_use]
+    pub fn new(list: Hittables, method: SplitMethod) -> Self {
+        Self::build(list.into_iter().collect(), method, Hittable::bounding_box)
+    }
+}
+
+impl Hittable for LinearBvh<Arc<dyn Hittable>> {
+    fn hit(&self, ray: &Ray, t: Interval) -> Option<HitRecord> {
+        self.hit_with(ray, t, |obj, t| obj.hit(ray, t))
+    }
+
+    fn bounding_box(&self) -> AABB { Self::bounding_box(self) }
+}
+
+impl From<Hittables> for LinearBvh<Arc<dyn Hittable>> {
+    fn from(list: Hittables) -> Self { Self::new(list, SplitMethod::default()) }
+}
+
+// ---------------------------------------------------------------------------
+// Builder
+// ---------------------------------------------------------------------------
+
+/// Centroid of a primitive's box as a degenerate (unpadded) box, so centroid
+/// bounds can be accumulated with `AABB::from((AABB, AABB))`.
+#[derive(Clone, Copy, Debug)]
+struct Centroid(AABB);
+
+impl Centroid {
+    fn of(bbox: AABB) -> Self {
+        let c = bbox.centroid();
+        Self(AABB { x: interval(c.x, c.x), y: interval(c.y, c.y), z: interval(c.z, c.z) })
+    }
+
+    const fn get(self, axis: Axis) -> f64 { self.0.get(axis).min }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct BuildItem {
+    /// Position in the caller's primitive list.
+    index: usize,
+    bbox: AABB,
+    centroid: Centroid,
+}
+
+/// Temporary pointer-based tree, flattened once the build is done.
+enum BuildNode {
+    Leaf { bbox: AABB, first: usize, count: usize },
+    Interior { bbox: AABB, axis: Axis, children: Box<(Self, Self)> },
+}
+
+impl BuildNode {
+    const fn bbox(&self) -> AABB {
+        match *self {
+            Self::Leaf { bbox, .. } | Self::Interior { bbox, .. } => bbox,
+        }
+    }
+}
+
+/// Builds the subtree over `items`, whose first element sits at `first` in
+/// the full item list.
+fn build_node(
+    items: &mut [BuildItem],
+    first: usize,
+    depth: usize,
+    method: SplitMethod,
+) -> BuildNode {
+    let bbox = items.iter().fold(AABB::EMPTY, |bbox, item| AABB::from((bbox, item.bbox)));
+    let count = items.len();
+
+    let split = if count <= 1 || depth + 1 >= MAX_DEPTH {
+        None
+    } else {
+        match method {
+            SplitMethod::Middle => Some(split_middle(items, bbox)),
+            SplitMethod::Sah { bins } => split_sah(items, bbox, bins),
+        }
+    };
+    let Some((axis, mid)) = split else {
+        return BuildNode::Leaf { bbox, first, count };
+    };
+
+    let (lo, hi) = items.split_at_mut(mid);
+    let (left, right) = if count > PARALLEL_THRESHOLD {
+        rayon::join(
+            || build_node(lo, first, depth + 1, method),
+            || build_node(hi, first + mid, depth + 1, method),
+        )
+    } else {
+        (build_node(lo, first, depth + 1, method), build_node(hi, first + mid, depth + 1, method))
+    };
+
+    BuildNode::Interior { bbox, axis, children: Box::new((left, right)) }
+}
+
+fn split_middle(items: &mut [BuildItem], bbox: AABB) -> (Axis, usize) {
+    let axis = bbox.longest_axis();
+    items.sort_by(|a, b| a.bbox.get(axis).min.total_cmp(&b.bbox.get(axis).min));
+    (axis, items.len().div_euclid(2))
+}
+
+/// A bin of the SAH sweep: the primitives whose centroids fall in one slab.
+#[derive(Clone, Copy, Debug, Default)]
+struct Bin {
+    bbox: AABB,
+    count: u32,
+}
+
+/// Returns the split axis and the number of items moved to the left child, or
+/// `None` if a leaf is cheaper.
+fn split_sah(items: &mut [BuildItem], bbox: AABB, bins: usize) -> Option<(Axis, usize)> {
+    let count = items.len();
+    let centroids =
+        items.iter().fold(AABB::EMPTY, |bounds, item| AABB::from((bounds, item.centroid.0)));
+    let parent_area = bbox.surface_area();
+    let parent_area = if parent_area > 0.0 { parent_area } else { 1.0 };
+
+    // (cost, axis, last bin on the left)
+    let mut best: Option<(f64, Axis, usize)> = None;
+    for axis in Axis::ALL {
+        let range = centroids.get(axis);
+        if range.size() <= 0.0 {
+            continue;
+        }
+
+        let mut slabs = vec![Bin::default(); bins];
+        for item in &*items {
+            if let Some(bin) = slabs.get_mut(bin_index(item.centroid.get(axis), range, bins)) {
+                bin.bbox = AABB::from((bin.bbox, item.bbox));
+                bin.count += 1;
+            }
+        }
+
+        // Sweep from the right to get the area and count of every suffix, then
+        // from the left evaluating each boundary.
+        let mut suffix = vec![Bin::default(); bins];
+        let mut acc = Bin::default();
+        for (slab, out) in slabs.iter().zip(suffix.iter_mut()).rev() {
+            acc = Bin { bbox: AABB::from((acc.bbox, slab.bbox)), count: acc.count + slab.count };
+            *out = acc;
+        }
+
+        let mut left = Bin::default();
+        for (split, (slab, right)) in slabs.iter().zip(suffix.iter().skip(1)).enumerate() {
+            left = Bin { bbox: AABB::from((left.bbox, slab.bbox)), count: left.count + slab.count };
+            if left.count == 0 || right.count == 0 {
+                continue;
+            }
+            let cost = TRAVERSAL_COST
+                + INTERSECTION_COST
+                    * (left.bbox.surface_area() * f64::from(left.count)
+                        + right.bbox.surface_area() * f64::from(right.count))
+                    / parent_area;
+            if best.is_none_or(|(best_cost, ..)| cost < best_cost) {
+                best = Some((cost, axis, split));
+            }
+        }
+    }
+
+    let Some((cost, axis, split)) = best else {
+        // All centroids coincide: binning cannot separate them.
+        return (count > MAX_LEAF_SIZE).then(|| split_middle(items, bbox));
+    };
+
+    let leaf_cost = INTERSECTION_COST * f64::from(to_u32(count));
+    if cost >= leaf_cost && count <= MAX_LEAF_SIZE {
+        return None;
+    }
+
+    let range = centroids.get(axis);
+    let mid = partition(items, |item| bin_index(item.centroid.get(axis), range, bins) <= split);
+    Some((axis, mid))
+}
+
+/// Moves every item satisfying `pred` to the front and returns how many did.
+fn partition(items: &mut [BuildItem], pred: impl Fn(&BuildItem) -> bool) -> usize {
+    let mut split = 0;
+    for i in 0..items.len() {
+        if items.get(i).is_some_and(&pred) {
+            items.swap(split, i);
+            split += 1;
+        }
+    }
+    split
+}
+
+#[expect(clippy::as_conversions, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn bin_index(value: f64, range: Interval, bins: usize) -> usize {
+    let relative = (value - range.min) / range.size();
+    ((relative * f64::from(to_u32(bins))) as usize).min(bins - 1)
+}
+
+fn flatten(
+    node: BuildNode,
+    nodes: &mut Vec<LinearNode>,
+    stats: &mut BvhStats,
+    depth: usize,
+    root_area: f64,
+) {
+    stats.node_count += 1;
+    let area_ratio = node.bbox().surface_area() / root_area;
+
+    match node {
+        BuildNode::Leaf { bbox, first, count } => {
+            let count = to_u32(count);
+            nodes.push(LinearNode { bbox, offset: to_u32(first), count, axis: Axis::X });
+            stats.leaf_count += 1;
+            stats.max_depth = stats.max_depth.max(depth);
+            stats.sah_cost += INTERSECTION_COST * f64::from(count) * area_ratio;
+        }
+        BuildNode::Interior { bbox, axis, children } => {
+            let at = nodes.len();
+            nodes.push(LinearNode { bbox, offset: 0, count: 0, axis });
+            stats.sah_cost += TRAVERSAL_COST * area_ratio;
+
+            let (left, right) = *children;
+            flatten(left, nodes, stats, depth + 1, root_area);
+            let second = to_u32(nodes.len());
+            if let Some(parent) = nodes.get_mut(at) {
+                parent.offset = second;
+            }
+            flatten(right, nodes, stats, depth + 1, root_area);
+        }
+    }
+}
+
+/// Node offsets and counts are bounded by the primitive count, which
+/// [`LinearBvh::build`] checks fits in a `u32`.
+#[expect(clippy::as_conversions, clippy::cast_possible_truncation)]
+const fn to_u32(n: usize) -> u32 { n as u32 }
+
+#[expect(clippy::as_conversions)]
+const fn to_usize(n: u32) -> usize { n as usize }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng as _;
+    use rand::rngs::StdRng;
+    use shared::random_range;
+
+    use super::*;
+    use crate::prelude::{Lambertian, Material, Point3, Sphere, Vec3, color, point3};
+
+    /// The random-spheres scene from the end of Book 1.
+    fn final_scene(rng: &mut StdRng) -> Hittables {
+        let material: Arc<dyn Material> = Arc::new(Lambertian::new(color(0.5, 0.5, 0.5)));
+        let mut world = Hittables::new();
+        world.add(Arc::new(Sphere::new(point3(0, -1000, 0), None, 1000.0, Arc::clone(&material))));
+        for a in -11..11 {
+            for b in -11..11 {
+                let center = point3(
+                    f64::from(a) + 0.9 * random_range(rng, 0.0, 1.0),
+                    0.2,
+                    f64::from(b) + 0.9 * random_range(rng, 0.0, 1.0),
+                );
+                world.add(Arc::new(Sphere::new(center, None, 0.2, Arc::clone(&material))));
+            }
+        }
+        for center in [point3(0, 1, 0), point3(-4, 1, 0), point3(4, 1, 0)] {
+            world.add(Arc::new(Sphere::new(center, None, 1.0, Arc::clone(&material))));
+        }
+        world
+    }
+
+    #[test]
+    fn scenario_matches_flat_list() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let flat = final_scene(&mut rng);
+
+        for method in [SplitMethod::Middle, SplitMethod::default()] {
+            let bvh = LinearBvh::new(flat.clone(), method);
+            assert_eq!(bvh.primitives().len(), flat.len());
+
+            for _ in 0..2_000 {
+                let origin = point3(13, 2, 3) + Vec3::random_range(&mut rng, -2.0, 2.0);
+                let target = Point3::random_range(&mut rng, -12.0, 12.0);
+                let ray = Ray::new(origin, target - origin, None);
+                let t = interval(0.001, f64::INFINITY);
+
+                match (flat.hit(&ray, t), bvh.hit(&ray, t)) {
+                    (None, None) => {}
+                    (Some(a), Some(b)) => {
+                        assert_eq!(a.t.to_bits(), b.t.to_bits());
+                        assert_eq!(a.p, b.p);
+                    }
+                    (a, b) => panic!("flat hit: {}, bvh hit: {}", a.is_some(), b.is_some()),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn scenario_sah_beats_middle_split() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let world = final_scene(&mut rng);
+
+        let middle = LinearBvh::new(world.clone(), SplitMethod::Middle).stats();
+        let sah = LinearBvh::new(world.clone(), SplitMethod::default()).stats();
+
+        assert_eq!(middle.leaf_count, world.len());
+        assert_eq!(middle.node_count, 2 * world.len() - 1);
+        assert!(sah.sah_cost < middle.sah_cost, "SAH: {sah}; middle: {middle}");
+    }
+
+    #[test]
+    fn scenario_empty_list_never_hits() {
+        let bvh = LinearBvh::new(Hittables::new(), SplitMethod::default());
+        let ray = Ray::new(Point3::ZERO, Vec3::NEG_Z, None);
+        assert!(bvh.hit(&ray, Interval::UNIVERSE).is_none());
+        assert_eq!(bvh.stats().node_count, 0);
+    }
+}
@@ -0,0 +1,17 @@
+//! Bounding Volume Hierarchy (BVH)
+//!
+//! A binary tree of [`AABB`][crate::prelude::AABB]s over the objects of a
+//! scene. A ray that misses a node's box cannot hit anything beneath it, so
+//! traversal skips whole subtrees instead of testing every object the way
+//! [`Hittables`][crate::prelude::Hittables] does.
+//!
+//! - [`BvhNode`] is the recursive tree from Book 2: simple, and handy as a
+//!   reference.
+//! - [`LinearBvh`] is built with a binned surface-area heuristic and stored as
+//!   a flat array of nodes, traversed with a small fixed-size stack.
+
+mod linear;
+mod node;
+
+pub use self::linear::{BvhStats, LinearBvh, SplitMethod};
+pub use self::node::BvhNode;
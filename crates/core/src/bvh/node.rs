@@ -0,0 +1,133 @@
+//! Pointer-based BVH with the Book 2 midpoint split.
+
+use std::sync::Arc;
+
+use crate::prelude::{AABB, HitRecord, Hittable, Hittables, Interval, Ray, interval};
+
+/// A node in a BVH built with the midpoint split from Book 2.
+///
+/// Children are themselves `Hittable`s: either further `BvhNode`s or the
+/// scene objects at the leaves. A node over a single object stores that
+/// object in both children.
+pub struct BvhNode {
+    left: Arc<dyn Hittable>,
+    right: Arc<dyn Hittable>,
+    bbox: AABB,
+}
+
+impl BvhNode {
+    /// Builds a hierarchy over every object in `list`.
+    #[must_use]
+    pub fn new(list: Hittables) -> Self {
+        let mut objects: Vec<_> = list.into_iter().collect();
+        Self::build(&mut objects)
+    }
+
+    /// Splits `objects` in half along the longest axis of their combined
+    /// bounding box, after sorting them by the minimum of each object's box on
+    /// that axis.
+    fn build(objects: &mut [Arc<dyn Hittable>]) -> Self {
+        let bbox =
+            objects.iter().fold(AABB::EMPTY, |bbox, obj| AABB::from((bbox, obj.bounding_box())));
+        let axis = bbox.longest_axis();
+
+        let (left, right): (Arc<dyn Hittable>, Arc<dyn Hittable>) = match *objects {
+            [] => {
+                let empty: Arc<dyn Hittable> = Arc::new(Hittables::new());
+                (Arc::clone(&empty), empty)
+            }
+            [ref only] => (Arc::clone(only), Arc::clone(only)),
+            [ref a, ref b] => (Arc::clone(a), Arc::clone(b)),
+            _ => {
+                // Stable sort keeps the original list order for coincident
+                // boxes.
+                objects.sort_by(|a, b| {
+                    a.bounding_box().get(axis).min.total_cmp(&b.bounding_box().get(axis).min)
+                });
+                let (lo, hi) = objects.split_at_mut(objects.len().div_euclid(2));
+                (Arc::new(Self::build(lo)), Arc::new(Self::build(hi)))
+            }
+        };
+
+        Self { left, right, bbox }
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, ray: &Ray, t: Interval) -> Option<HitRecord> {
+        if !self.bbox.hit(ray, t) {
+            return None;
+        }
+
+        let hit_left = self.left.hit(ray, t);
+        // Shrink the interval so the right subtree only reports closer hits.
+        let closest = hit_left.as_ref().map_or(t.max, |rec| rec.t);
+        let hit_right = self.right.hit(ray, interval(t.min, closest));
+
+        hit_right.or(hit_left)
+    }
+
+    fn bounding_box(&self) -> AABB { self.bbox }
+}
+
+impl From<Hittables> for BvhNode {
+    fn from(list: Hittables) -> Self { Self::new(list) }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng as _;
+    use rand::rngs::StdRng;
+    use shared::random_range;
+
+    use super::*;
+    use crate::prelude::{Lambertian, Material, Point3, Sphere, Vec3, color, point3};
+
+    fn sphere_scene(rng: &mut StdRng, count: usize) -> Hittables {
+        let material: Arc<dyn Material> = Arc::new(Lambertian::new(color(0.5, 0.5, 0.5)));
+        let mut world = Hittables::new();
+        for _ in 0..count {
+            let center = Point3::random_range(rng, -20.0, 20.0);
+            let moving = (random_range(rng, 0.0, 1.0) < 0.2)
+                .then(|| center + Vec3::random_range(rng, -0.5, 0.5));
+            let radius = random_range(rng, 0.1, 1.5);
+            world.add(Arc::new(Sphere::new(center, moving, radius, Arc::clone(&material))));
+        }
+        world
+    }
+
+    #[test]
+    fn scenario_matches_flat_list() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let flat = sphere_scene(&mut rng, 500);
+        let bvh = BvhNode::new(flat.clone());
+
+        for _ in 0..2_000 {
+            let origin = Point3::random_range(&mut rng, -30.0, 30.0);
+            let target = point3(0, 0, 0) + Vec3::random_range(&mut rng, -10.0, 10.0);
+            let ray = Ray::new(origin, target - origin, Some(random_range(&mut rng, 0.0, 1.0)));
+            let t = interval(0.001, f64::INFINITY);
+
+            match (flat.hit(&ray, t), bvh.hit(&ray, t)) {
+                (None, None) => {}
+                (Some(a), Some(b)) => {
+                    assert_eq!(a.t.to_bits(), b.t.to_bits());
+                    assert_eq!(a.p, b.p);
+                    assert_eq!(a.normal, b.normal);
+                }
+                (a, b) => panic!("flat hit: {}, bvh hit: {}", a.is_some(), b.is_some()),
+            }
+        }
+    }
+
+    #[test]
+    fn scenario_empty_list_never_hits() {
+        let bvh = BvhNode::new(Hittables::new());
+        let ray = Ray::new(Point3::ZERO, Vec3::NEG_Z, None);
+        assert!(bvh.hit(&ray, Interval::UNIVERSE).is_none());
+    }
+}
@@ -1,4 +1,5 @@
-use std::io::{self, BufWriter, Write as _};
+use std::io::{self, BufWriter};
+use std::sync::Arc;
 
 use rand::prelude::*;
 use rayon::prelude::*;
@@ -11,7 +12,7 @@ use crate::prelude::*;
 /// Construct with `Camera { ..Default::default() }` and override the fields
 /// you care about. The `render()` method will pre-compute the derived geometry
 /// and then render the scene.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct Camera {
     /// Image width / height ratio.
     pub aspect_ratio: f64,
@@ -19,7 +20,7 @@ pub struct Camera {
     pub image_width: u32,
     /// Number of random samples per pixel (anti-aliasing).
     pub samples_per_pixel: u32,
-    /// Maximum ray-bounce depth.
+    /// Maximum ray-bounce depth; see [`PathIntegrator::max_depth`].
     pub max_depth: u32,
     /// Vertical field of view in degrees.
     pub vfov: f64,
@@ -33,6 +34,24 @@ pub struct Camera {
     pub defocus_angle: f64,
     /// Distance from `lookfrom` to the plane of perfect focus.
     pub focus_dist: f64,
+    /// Scene time at which the shutter opens. Each ray gets a uniformly
+    /// random time between this and `shutter_close`.
+    pub shutter_open: f64,
+    /// Scene time at which the shutter closes. Set equal to `shutter_open`
+    /// to freeze all motion at that instant.
+    pub shutter_close: f64,
+    /// Radiance of rays that escape the scene. Defaults to the books' sky;
+    /// use a solid black for scenes lit only by emissive materials.
+    pub background: Background,
+    /// Applied to every pixel once rendering finishes. Leave as `None` to
+    /// keep raw radiance, e.g. for HDR output.
+    pub tone_mapper: Option<Arc<dyn ToneMapper>>,
+    /// Encoding of the PPM written by [`Self::render`].
+    pub transfer: TransferFunction,
+    /// How [`Self::render_to_image_with_lights`] uses its lights.
+    pub light_sampling: LightSampling,
+    /// How each camera ray's path is followed.
+    pub path_tracing: PathTracing,
 }
 
 impl Default for Camera {
@@ -48,6 +67,13 @@ impl Default for Camera {
             vup: Vec3::Y,
             defocus_angle: 0.0,
             focus_dist: 10.0,
+            shutter_open: 0.0,
+            shutter_close: 1.0,
+            background: Background::SKY,
+            tone_mapper: None,
+            transfer: TransferFunction::Gamma2,
+            light_sampling: LightSampling::Mixture,
+            path_tracing: PathTracing::Recursive,
         }
     }
 }
@@ -80,43 +106,87 @@ struct CameraState {
 
 impl Camera {
     /// Renders the scene and writes PPM output to **stdout**.
-    ///
-    /// Row-level parallelism via `rayon` — each scanline is independent.
-    /// The pixel buffer is collected into a `Vec` first so I/O stays serial
-    /// (writing to stdout from multiple threads would require a Mutex).
     pub fn render(&self, world: &dyn Hittable) -> io::Result<()> {
-        let state = self.initialize();
+        let image = self.render_to_image(world);
 
         let mut out = BufWriter::new(io::stdout().lock());
-        writeln!(out, "P3\n{} {}\n255", self.image_width, state.image_height)?;
+        PpmWriter::default().with_transfer(self.transfer).write(&image, &mut out)?;
+
+        eprintln!("\rDone.        ");
+        Ok(())
+    }
+
+    /// Renders the scene into an in-memory [`Image`] of linear colours.
+    ///
+    /// Row-level parallelism via `rayon` — each scanline is independent and
+    /// writes only to its own slice of the pixel buffer.
+    #[must_use]
+    pub fn render_to_image(&self, world: &dyn Hittable) -> Image {
+        let scene = Scene::new(world, &self.background);
+        self.render_with_integrator(&self.path_integrator(), &scene)
+    }
+
+    /// Like [`Self::render_to_image`], but diffuse bounces also sample
+    /// `lights` directly, as chosen by [`Self::light_sampling`]; see
+    /// [`LightSampling`]. This converges much faster when small emitters
+    /// light the scene. `lights` should hold the emissive objects; geometry
+    /// that doesn't implement [`Hittable::random`] is only found by chance.
+    #[must_use]
+    pub fn render_to_image_with_lights(
+        &self,
+        world: &dyn Hittable,
+        lights: &dyn Hittable,
+    ) -> Image {
+        let scene = Scene::new(world, &self.background).with_lights(lights);
+        self.render_with_integrator(&self.path_integrator(), &scene)
+    }
+
+    /// Renders `scene` with any [`Integrator`], such as a
+    /// [`DirectLighting`] preview or a [`DebugView`]. The scene's background
+    /// replaces [`Self::background`], and the integrator's own settings
+    /// replace `max_depth`, `light_sampling` and `path_tracing`.
+    #[must_use]
+    pub fn render_with_integrator(&self, integrator: &dyn Integrator, scene: &Scene<'_>) -> Image {
+        let state = self.initialize();
+        let mut image = Image::new(self.image_width, state.image_height);
 
         // Parallelise over rows. Each row creates its own RNG so there are no
         // shared mutable state or lock contention issues.
-        let pixels: Vec<_> = (0..state.image_height)
-            .into_par_iter()
-            .flat_map(|row| {
+        image
+            .pixels_mut()
+            .par_chunks_mut(self.image_width.try_into().unwrap_or(1).max(1))
+            .zip(0..state.image_height)
+            .for_each(|(pixels, row)| {
                 let mut rng = rand::rng();
-                (0..self.image_width)
-                    .map(|col| {
-                        // Accumulate `samples_per_pixel` jittered rays, then scale.
-                        let pixel_color: Color3 = core::iter::repeat_with(|| {
-                            let ray = self.get_ray(&state, &mut rng, col, row);
-                            Self::ray_color(&mut rng, &ray, self.max_depth, world)
-                        })
-                        .take(self.samples_per_pixel.try_into().unwrap_or(0))
-                        .sum();
-                        state.pixel_samples_scale * pixel_color
+                for (col, pixel) in (0..self.image_width).zip(pixels) {
+                    // Accumulate `samples_per_pixel` jittered rays, then scale.
+                    let pixel_color: Color3 = core::iter::repeat_with(|| {
+                        let ray = self.get_ray(&state, &mut rng, col, row);
+                        integrator.li(&ray, scene, &mut rng)
                     })
-                    .collect::<Vec<_>>()
-            })
-            .collect();
+                    .take(self.samples_per_pixel.try_into().unwrap_or(0))
+                    .sum();
+                    *pixel = state.pixel_samples_scale * pixel_color;
+                }
+            });
 
-        for pixel in pixels {
-            writeln!(out, "{pixel}")?;
+        if let Some(mapper) = self.tone_mapper.as_deref() {
+            image.tone_map(mapper);
         }
+        image
+    }
 
-        eprintln!("\rDone.        ");
-        Ok(())
+    /// The path tracer that [`Self::render_to_image`] and
+    /// [`Self::render_to_image_with_lights`] use, set up from this camera's
+    /// fields.
+    #[inline]
+    #[must_use]
+    pub const fn path_integrator(&self) -> PathIntegrator {
+        PathIntegrator {
+            max_depth: self.max_depth,
+            light_sampling: self.light_sampling,
+            path_tracing: self.path_tracing,
+        }
     }
 
     /// Computes a ray from the camera through the pixel at `(col, row)`.
@@ -139,30 +209,8 @@ impl Camera {
             s.center + (p.x * s.defocus_disk_u) + (p.y * s.defocus_disk_v)
         };
 
-        Ray::new(origin, pixel_sample - origin, Some(random(rng)))
-    }
-
-    /// Recursively traces `ray` and returns the accumulated radiance.
-    ///
-    /// The recursion terminates either at `depth == 0` (absorb all light) or
-    /// when a ray escapes to the sky gradient.
-    fn ray_color(rng: &mut dyn Rng, ray: &Ray, depth: u32, world: &dyn Hittable) -> Color3 {
-        if depth == 0 {
-            return Color3::BLACK;
-        }
-
-        // t_min = 0.001 avoids "shadow acne": self-intersection due to the hit
-        // point floating slightly inside the surface.
-        if let Some(rec) = world.hit(ray, interval(0.001, f64::INFINITY)) {
-            if let Some((attenuation, scattered)) = rec.material.scatter(rng, ray, &rec) {
-                return attenuation * Self::ray_color(rng, &scattered, depth - 1, world);
-            }
-            return Color3::BLACK;
-        }
-
-        // Sky gradient: white at the horizon, light blue at the top.
-        let a = 0.5 * (ray.direction.unit().y + 1.0);
-        (1.0 - a) * Color3::WHITE + a * color(0.5, 0.7, 1.0)
+        let time = self.shutter_open + random(rng) * (self.shutter_close - self.shutter_open);
+        Ray::new(origin, pixel_sample - origin, Some(time))
     }
 
     /// Pre-computes all camera geometry from the user-facing parameters.
@@ -219,3 +267,70 @@ impl Camera {
         }
     }
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_camera() -> Camera {
+        Camera {
+            image_width: 4,
+            samples_per_pixel: 2,
+            background: Background::Solid(Color3::BLACK),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn scenario_black_background_without_lights_is_black() {
+        let world = Hittables::new();
+        let image = small_camera().render_to_image(&world);
+        assert!(image.pixels().iter().all(|&p| p == Color3::BLACK));
+    }
+
+    #[test]
+    fn scenario_emitters_light_a_black_background() {
+        // The camera sits inside a glowing sphere, so every ray sees it.
+        let light: Arc<dyn Material> = Arc::new(DiffuseLight::new(color(4.0, 2.0, 1.0)));
+        let world = Hittables::from(vec![Sphere::new(Point3::ZERO, None, 10.0, light)]);
+
+        let image = small_camera().render_to_image(&world);
+        assert!(image.pixels().iter().all(|&p| p == color(4.0, 2.0, 1.0)));
+    }
+
+    #[test]
+    fn scenario_shutter_selects_the_moment() {
+        // The glowing sphere around the camera flies off during [0, 1].
+        let light: Arc<dyn Material> = Arc::new(DiffuseLight::new(color(1.0, 1.0, 1.0)));
+        let sphere = Arc::new(Sphere::new(Point3::ZERO, None, 10.0, light));
+        let animation = AnimatedTransform::new(vec![
+            Keyframe::at(0.0),
+            Keyframe::at(1.0).with_translation(vec3(100, 0, 0)),
+        ]);
+        let world = Hittables::from(vec![Animated::new(sphere, animation)]);
+
+        let at = |time: f64| Camera { shutter_open: time, shutter_close: time, ..small_camera() };
+        let start = at(0.0).render_to_image(&world);
+        assert!(start.pixels().iter().all(|&p| p == color(1.0, 1.0, 1.0)));
+        let end = at(1.0).render_to_image(&world);
+        assert!(end.pixels().iter().all(|&p| p == Color3::BLACK));
+    }
+
+    #[test]
+    fn scenario_integrator_selected_per_render() {
+        // From the centre of a sphere of radius 10, every ray travels 10.
+        let grey: Arc<dyn Material> = Arc::new(Lambertian::new(color(0.5, 0.5, 0.5)));
+        let world = Hittables::from(vec![Sphere::new(Point3::ZERO, None, 10.0, grey)]);
+        let camera = small_camera();
+        let scene = Scene::new(&world, &camera.background);
+
+        let depth = camera.render_with_integrator(&DebugView::Depth { far: 20.0 }, &scene);
+        assert!(depth.pixels().iter().all(|p| (p.r - 0.5).abs() < 1e-9));
+        let albedo = camera.render_with_integrator(&DebugView::Albedo, &scene);
+        assert!(albedo.pixels().iter().all(|&p| p == color(0.5, 0.5, 0.5)));
+    }
+}
@@ -38,6 +38,11 @@ impl Color3 {
     #[must_use]
     pub const fn splat(v: f64) -> Self { Self { r: v, g: v, b: v } }
 
+    /// Relative luminance with Rec. 709 / sRGB primaries.
+    #[inline]
+    #[must_use]
+    pub const fn luminance(self) -> f64 { 0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b }
+
     #[must_use]
     pub const fn get(self, channel: Channel) -> f64 {
         match channel {
@@ -84,6 +89,211 @@ pub fn linear_to_gamma(component: f64) -> f64 {
     if component > 0.0 { component.sqrt() } else { 0.0 }
 }
 
+/// Inverse of [`linear_to_gamma`]: squares a gamma-2 encoded component.
+#[inline]
+#[must_use]
+pub fn gamma_to_linear(component: f64) -> f64 {
+    if component > 0.0 { component * component } else { 0.0 }
+}
+
+// ---------------------------------------------------------------------------
+// Transfer functions
+// ---------------------------------------------------------------------------
+
+/// Encodes linear display values for 8- and 16-bit output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TransferFunction {
+    /// Square root, as in the books. Keeps the reference images unchanged.
+    #[default]
+    Gamma2,
+    /// The piecewise sRGB OETF (IEC 61966-2-1).
+    Srgb,
+    /// No encoding.
+    Linear,
+}
+
+impl TransferFunction {
+    /// Encodes a linear component; negative input maps to 0.
+    #[must_use]
+    pub fn encode(self, component: f64) -> f64 {
+        match self {
+            Self::Gamma2 => linear_to_gamma(component),
+            Self::Srgb => linear_to_srgb(component),
+            Self::Linear => component.max(0.0),
+        }
+    }
+
+    /// Inverse of [`Self::encode`].
+    #[must_use]
+    pub fn decode(self, component: f64) -> f64 {
+        match self {
+            Self::Gamma2 => gamma_to_linear(component),
+            Self::Srgb => srgb_to_linear(component),
+            Self::Linear => component.max(0.0),
+        }
+    }
+
+    /// Encodes and quantises to bytes, clipping anything above 1.
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::as_conversions)]
+    #[must_use]
+    pub fn to_u8(self, c: Color3) -> [u8; 3] {
+        // Map [0, 0.999] → [0, 255].
+        [c.r, c.g, c.b].map(|v| (FLOAT_TO_BYTE * INTENSITY.clamp(self.encode(v))) as u8)
+    }
+
+    /// 16-bit counterpart of [`Self::to_u8`], for deep PNG output.
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::as_conversions)]
+    #[must_use]
+    pub fn to_u16(self, c: Color3) -> [u16; 3] {
+        [c.r, c.g, c.b].map(|v| (f64::from(u16::MAX) * self.encode(v).min(1.0)).round() as u16)
+    }
+}
+
+/// The sRGB OETF: linear below 0.0031308, a 1/2.4 power curve above.
+#[must_use]
+pub fn linear_to_srgb(component: f64) -> f64 {
+    if component <= 0.0 {
+        0.0
+    } else if component <= 0.003_130_8 {
+        12.92 * component
+    } else {
+        1.055 * component.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Inverse of [`linear_to_srgb`].
+#[must_use]
+pub fn srgb_to_linear(component: f64) -> f64 {
+    if component <= 0.0 {
+        0.0
+    } else if component <= 0.040_45 {
+        component / 12.92
+    } else {
+        ((component + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tone mapping
+// ---------------------------------------------------------------------------
+
+/// Compresses unbounded scene radiance into the displayable `[0, 1]` range.
+///
+/// Operators work on linear values; the [`TransferFunction`] is applied
+/// afterwards by the writer.
+pub trait ToneMapper: fmt::Debug + Send + Sync {
+    fn map(&self, c: Color3) -> Color3;
+}
+
+/// Scales by `2^stops` and leaves clipping to the writer.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Exposure {
+    pub stops: f64,
+}
+
+impl ToneMapper for Exposure {
+    fn map(&self, c: Color3) -> Color3 { self.stops.exp2() * c }
+}
+
+/// Reinhard's global operator `L / (1 + L)` on luminance, which keeps hue.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Reinhard;
+
+impl ToneMapper for Reinhard {
+    fn map(&self, c: Color3) -> Color3 { scale_luminance(c, |l| l / (1.0 + l)) }
+}
+
+/// Reinhard with a white point: luminance `white` and above maps to 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReinhardExtended {
+    pub white: f64,
+}
+
+impl Default for ReinhardExtended {
+    fn default() -> Self { Self { white: 4.0 } }
+}
+
+impl ToneMapper for ReinhardExtended {
+    fn map(&self, c: Color3) -> Color3 {
+        let white_sq = self.white * self.white;
+        scale_luminance(c, |l| (l * (1.0 + l / white_sq) / (1.0 + l)).min(1.0))
+    }
+}
+
+/// John Hable's filmic curve from Uncharted 2, applied per channel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hable {
+    /// Multiplier applied before the curve.
+    pub exposure_bias: f64,
+    /// Linear value that maps to 1.
+    pub white: f64,
+}
+
+impl Default for Hable {
+    fn default() -> Self { Self { exposure_bias: 2.0, white: 11.2 } }
+}
+
+impl Hable {
+    fn curve(x: f64) -> f64 {
+        const A: f64 = 0.15; // shoulder strength
+        const B: f64 = 0.50; // linear strength
+        const C: f64 = 0.10; // linear angle
+        const D: f64 = 0.20; // toe strength
+        const E: f64 = 0.02; // toe numerator
+        const F: f64 = 0.30; // toe denominator
+        (x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F) - E / F
+    }
+}
+
+impl ToneMapper for Hable {
+    fn map(&self, c: Color3) -> Color3 {
+        let scale = 1.0 / Self::curve(self.white);
+        let f = |v: f64| (Self::curve(self.exposure_bias * v.max(0.0)) * scale).min(1.0);
+        Color3::new(f(c.r), f(c.g), f(c.b))
+    }
+}
+
+/// Stephen Hill's fit of the ACES reference rendering and sRGB output
+/// transforms.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AcesFitted;
+
+impl AcesFitted {
+    /// sRGB → ACES AP1 with the RRT saturation adjustment folded in.
+    const INPUT: [[f64; 3]; 3] =
+        [[0.59719, 0.35458, 0.04823], [0.07600, 0.90834, 0.01566], [0.02840, 0.13383, 0.83777]];
+    /// ODT saturation and AP1 → sRGB.
+    const OUTPUT: [[f64; 3]; 3] = [[1.60475, -0.53108, -0.07367], [-0.10208, 1.10813, -0.00605], [
+        -0.00327, -0.07276, 1.07602,
+    ]];
+
+    fn transform(m: [[f64; 3]; 3], c: Color3) -> Color3 {
+        let [r, g, b] = m.map(|row| row[0] * c.r + row[1] * c.g + row[2] * c.b);
+        Color3::new(r, g, b)
+    }
+
+    fn rrt_and_odt(v: f64) -> f64 {
+        let a = v * (v + 0.024_578_6) - 0.000_090_537;
+        let b = v * (0.983_729 * v + 0.432_951) + 0.238_081;
+        a / b
+    }
+}
+
+impl ToneMapper for AcesFitted {
+    fn map(&self, c: Color3) -> Color3 {
+        let c = Self::transform(Self::INPUT, c);
+        let c = Color3::new(Self::rrt_and_odt(c.r), Self::rrt_and_odt(c.g), Self::rrt_and_odt(c.b));
+        let c = Self::transform(Self::OUTPUT, c);
+        Color3::new(c.r.clamp(0.0, 1.0), c.g.clamp(0.0, 1.0), c.b.clamp(0.0, 1.0))
+    }
+}
+
+/// Rescales `c` so its luminance becomes `curve(luminance)`.
+fn scale_luminance(c: Color3, curve: impl Fn(f64) -> f64) -> Color3 {
+    let l = c.luminance();
+    if l > 0.0 { (curve(l) / l) * c } else { Color3::BLACK }
+}
+
 // ---------------------------------------------------------------------------
 // Conversion to/from [u8; 3]
 // ---------------------------------------------------------------------------
@@ -96,15 +306,18 @@ const INTENSITY: Interval = Interval::new(0.0, 0.999);
 const FLOAT_TO_BYTE: f64 = 256.0;
 const BYTE_TO_FLOAT: f64 = 1.0 / 255.0;
 
-#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::as_conversions)]
 impl From<Color3> for [u8; 3] {
-    fn from(c: Color3) -> Self {
-        // Apply gamma correction then map [0, 0.999] → [0, 255].
-        [c.r, c.g, c.b].map(|v| (FLOAT_TO_BYTE * INTENSITY.clamp(linear_to_gamma(v))) as u8)
-    }
+    /// Gamma-2 encoding, as written by the books.
+    fn from(c: Color3) -> Self { TransferFunction::Gamma2.to_u8(c) }
+}
+
+impl From<Color3> for [u16; 3] {
+    fn from(c: Color3) -> Self { TransferFunction::Gamma2.to_u16(c) }
 }
 
 impl From<[u8; 3]> for Color3 {
+    /// Scales bytes to `[0, 1]` without decoding any transfer function; see
+    /// [`TransferFunction::decode`] for linearising image data.
     fn from([r, g, b]: [u8; 3]) -> Self {
         let [r, g, b] = [r, g, b].map(|v| f64::from(v) * BYTE_TO_FLOAT);
         Self::new(r, g, b)
@@ -231,3 +444,49 @@ impl Sum for Color3 {
 impl Product for Color3 {
     fn product<I: Iterator<Item = Self>>(iter: I) -> Self { iter.fold(Self::WHITE, Self::mul) }
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenario_srgb_round_trip() {
+        for v in [0.0, 0.001, 0.003_130_8, 0.2, 0.5, 1.0] {
+            shared::assert_fuzzy_eq!(srgb_to_linear(linear_to_srgb(v)), v);
+        }
+        shared::assert_fuzzy_eq!(linear_to_srgb(1.0), 1.0);
+        assert_eq!(TransferFunction::Srgb.to_u8(color(0.214_041, 0.0, 1.0)), [127, 0, 255]);
+    }
+
+    #[test]
+    fn scenario_tone_mappers_are_bounded_and_monotonic() {
+        let mappers: [&dyn ToneMapper; 4] =
+            [&Reinhard, &ReinhardExtended::default(), &Hable::default(), &AcesFitted];
+        for mapper in mappers {
+            let mut previous = -1.0;
+            for i in 0..200 {
+                let v = 0.05 * f64::from(i);
+                let mapped = mapper.map(Color3::splat(v));
+                assert!(
+                    (0.0..=1.0 + 1e-12).contains(&mapped.g),
+                    "{mapper:?} maps {v} to {}",
+                    mapped.g
+                );
+                assert!(mapped.g >= previous - 1e-12, "{mapper:?} is not monotonic at {v}");
+                previous = mapped.g;
+            }
+            assert_eq!(mapper.map(Color3::BLACK), Color3::BLACK);
+        }
+    }
+
+    #[test]
+    fn scenario_white_points() {
+        shared::assert_fuzzy_eq!(ReinhardExtended { white: 4.0 }.map(Color3::splat(4.0)).r, 1.0);
+        shared::assert_fuzzy_eq!(Hable::default().map(Color3::splat(5.6)).r, 1.0);
+        assert_eq!(Exposure { stops: 1.0 }.map(Color3::splat(0.25)), Color3::splat(0.5));
+    }
+}
@@ -1,9 +1,10 @@
+use core::f64::consts::TAU;
 use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use rand::prelude::*;
 use shared::{random, random_range};
 
-use crate::prelude::Axis;
+use crate::prelude::{AABB, Axis, Interval, Ray, interval};
 
 /// This accepts anything `Into<f64>` so you can write
 /// `vec3(0, 1, -2)` instead of `vec3(0.0, 1.0, -2.0)`.
@@ -167,6 +168,28 @@ impl Vec3 {
         if v.dot(normal) > 0.0 { v } else { -v }
     }
 
+    /// Returns a random unit vector around +z with density cos θ / π, where θ
+    /// is its angle to +z.
+    #[must_use]
+    pub fn random_cosine_direction(rng: &mut dyn Rng) -> Self {
+        let (r1, r2) = (random(rng), random(rng));
+        let (sin, cos) = (TAU * r1).sin_cos();
+        Self::new(cos * r2.sqrt(), sin * r2.sqrt(), (1.0 - r2).sqrt())
+    }
+
+    /// Returns a random unit vector around +z, uniform over the cone of
+    /// directions towards a sphere of `radius` whose centre lies
+    /// `distance_squared` away along +z.
+    #[must_use]
+    pub fn random_to_sphere(rng: &mut dyn Rng, radius: f64, distance_squared: f64) -> Self {
+        let (r1, r2) = (random(rng), random(rng));
+        let cos_max = (1.0 - radius * radius / distance_squared).max(0.0).sqrt();
+        let z = 1.0 + r2 * (cos_max - 1.0);
+        let (sin, cos) = (TAU * r1).sin_cos();
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        Self::new(cos * r, sin * r, z)
+    }
+
     /// Returns a random point inside the unit disk (z = 0).
     ///
     /// Used for defocus / depth-of-field sampling.
@@ -181,6 +204,39 @@ impl Vec3 {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Orthonormal basis
+// ---------------------------------------------------------------------------
+
+/// A right-handed orthonormal basis whose `w` axis points along a given
+/// direction, for sampling directions around a normal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    /// `n` need not be unit length.
+    #[must_use]
+    pub fn new(n: Vec3) -> Self {
+        let w = n.unit();
+        // Any axis not parallel to `w` will do.
+        let a = if w.x.abs() > 0.9 { Vec3::Y } else { Vec3::X };
+        let v = w.cross(a).unit();
+        let u = w.cross(v);
+        Self { u, v, w }
+    }
+
+    /// Maps `local` from basis coordinates into world space.
+    #[inline]
+    #[must_use]
+    pub const fn transform(&self, local: Vec3) -> Vec3 {
+        local.x * self.u + local.y * self.v + local.z * self.w
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Point3 alias
 // ---------------------------------------------------------------------------
@@ -296,3 +352,423 @@ impl const DivAssign<f64> for Vec3 {
     #[inline]
     fn div_assign(&mut self, rhs: f64) { *self *= 1.0 / rhs; }
 }
+
+// ---------------------------------------------------------------------------
+// Transform
+// ---------------------------------------------------------------------------
+
+/// The top three rows of a 4×4 affine matrix. The bottom row is always
+/// `[0 0 0 1]`, so it is left implicit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Affine {
+    rows: [Vec3; 3],
+    offset: Vec3,
+}
+
+impl Affine {
+    const IDENTITY: Self = Self::linear(Vec3::AXES);
+
+    const fn linear(rows: [Vec3; 3]) -> Self { Self { rows, offset: Vec3::ZERO } }
+
+    const fn apply(&self, v: Vec3) -> Vec3 {
+        let [r0, r1, r2] = self.rows;
+        Vec3::new(r0.dot(v), r1.dot(v), r2.dot(v))
+    }
+
+    /// The rows of the transposed linear part.
+    const fn transpose(&self) -> [Vec3; 3] {
+        let [r0, r1, r2] = self.rows;
+        [Vec3::new(r0.x, r1.x, r2.x), Vec3::new(r0.y, r1.y, r2.y), Vec3::new(r0.z, r1.z, r2.z)]
+    }
+
+    /// `self × rhs`, which applies `rhs` first.
+    fn compose(&self, rhs: &Self) -> Self {
+        let [b0, b1, b2] = rhs.rows;
+        Self {
+            rows: self.rows.map(|r| r.x * b0 + r.y * b1 + r.z * b2),
+            offset: self.apply(rhs.offset) + self.offset,
+        }
+    }
+
+    /// `None` if the linear part is singular.
+    fn inverse(&self) -> Option<Self> {
+        let [r0, r1, r2] = self.rows;
+        let det = r0.dot(r1.cross(r2));
+        if !det.is_normal() {
+            return None;
+        }
+        // The columns of the inverse are cross products of the rows.
+        let columns = Self::linear([r1.cross(r2) / det, r2.cross(r0) / det, r0.cross(r1) / det]);
+        let inverse = Self::linear(columns.transpose());
+        Some(Self { offset: -inverse.apply(self.offset), ..inverse })
+    }
+}
+
+/// An affine transform: a 4×4 matrix whose bottom row is `[0 0 0 1]`, kept
+/// together with its inverse.
+///
+/// Points pick up the translation and vectors do not. Normals go through the
+/// inverse transpose, so they stay perpendicular to transformed surfaces.
+/// `a * b` applies `b` first; `a.then(b)` applies `a` first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    m: Affine,
+    inv: Affine,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self { m: Affine::IDENTITY, inv: Affine::IDENTITY };
+
+    #[inline]
+    #[must_use]
+    pub const fn translate(offset: Vec3) -> Self {
+        Self {
+            m: Affine { rows: Vec3::AXES, offset },
+            inv: Affine { rows: Vec3::AXES, offset: -offset },
+        }
+    }
+
+    /// Scales about the origin. Every factor must be non-zero.
+    #[must_use]
+    pub const fn scale(factors: Vec3) -> Self {
+        const fn diagonal(d: Vec3) -> Affine {
+            Affine::linear([
+                Vec3::new(d.x, 0.0, 0.0),
+                Vec3::new(0.0, d.y, 0.0),
+                Vec3::new(0.0, 0.0, d.z),
+            ])
+        }
+        let reciprocal = Vec3::new(1.0 / factors.x, 1.0 / factors.y, 1.0 / factors.z);
+        Self { m: diagonal(factors), inv: diagonal(reciprocal) }
+    }
+
+    /// Rotates by `degrees` about `axis`, which passes through the origin and
+    /// need not be unit length. Positive angles turn counter-clockwise when
+    /// looking down the axis towards the origin.
+    #[must_use]
+    pub fn rotate(axis: Vec3, degrees: f64) -> Self {
+        let Vec3 { x, y, z } = axis.unit();
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let c = 1.0 - cos;
+        let m = Affine::linear([
+            Vec3::new(cos + x * x * c, x * y * c - z * sin, x * z * c + y * sin),
+            Vec3::new(y * x * c + z * sin, cos + y * y * c, y * z * c - x * sin),
+            Vec3::new(z * x * c - y * sin, z * y * c + x * sin, cos + z * z * c),
+        ]);
+        // A rotation is undone by its transpose.
+        Self { m, inv: Affine::linear(m.transpose()) }
+    }
+
+    /// Builds a transform from a row-major matrix. Returns `None` unless the
+    /// bottom row is `[0 0 0 1]` and the matrix is invertible.
+    #[must_use]
+    #[expect(clippy::float_cmp, reason = "an affine bottom row is exactly [0 0 0 1]")]
+    pub fn from_matrix(matrix: [[f64; 4]; 4]) -> Option<Self> {
+        let [r0, r1, r2, bottom] = matrix;
+        if bottom != [0.0, 0.0, 0.0, 1.0] {
+            return None;
+        }
+        let linear = |[x, y, z, _]: [f64; 4]| Vec3::new(x, y, z);
+        let m = Affine {
+            rows: [linear(r0), linear(r1), linear(r2)],
+            offset: Vec3::new(r0[3], r1[3], r2[3]),
+        };
+        Some(Self { m, inv: m.inverse()? })
+    }
+
+    /// The row-major matrix.
+    #[must_use]
+    pub const fn matrix(&self) -> [[f64; 4]; 4] {
+        let ([r0, r1, r2], o) = (self.m.rows, self.m.offset);
+        [[r0.x, r0.y, r0.z, o.x], [r1.x, r1.y, r1.z, o.y], [r2.x, r2.y, r2.z, o.z], [
+            0.0, 0.0, 0.0, 1.0,
+        ]]
+    }
+
+    /// The inverse transpose of the upper-left 3×3 block, row-major. This is
+    /// what [`Transform::normal`] multiplies by.
+    #[must_use]
+    pub const fn normal_matrix(&self) -> [[f64; 3]; 3] {
+        let [r0, r1, r2] = self.inv.transpose();
+        [[r0.x, r0.y, r0.z], [r1.x, r1.y, r1.z], [r2.x, r2.y, r2.z]]
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn inverse(&self) -> Self { Self { m: self.inv, inv: self.m } }
+
+    /// The determinant of the upper-left 3×3 block: the factor by which the
+    /// transform scales volumes.
+    #[inline]
+    #[must_use]
+    pub fn determinant(&self) -> f64 {
+        let [r0, r1, r2] = self.m.rows;
+        r0.dot(r1.cross(r2))
+    }
+
+    /// Applies `self`, then `next`.
+    #[inline]
+    #[must_use]
+    pub fn then(&self, next: &Self) -> Self { *next * *self }
+
+    #[inline]
+    #[must_use]
+    pub const fn point(&self, p: Point3) -> Point3 { self.m.apply(p) + self.m.offset }
+
+    #[inline]
+    #[must_use]
+    pub const fn vector(&self, v: Vec3) -> Vec3 { self.m.apply(v) }
+
+    /// Transforms a surface normal. The result is not unit length in general.
+    #[inline]
+    #[must_use]
+    pub const fn normal(&self, n: Vec3) -> Vec3 {
+        let [r0, r1, r2] = self.inv.rows;
+        n.x * r0 + n.y * r1 + n.z * r2
+    }
+
+    /// Transforms the origin as a point and the direction as a vector. The
+    /// direction is not renormalised, so ray parameters carry over unchanged.
+    #[inline]
+    #[must_use]
+    pub const fn ray(&self, ray: &Ray) -> Ray {
+        Ray {
+            origin: self.point(ray.origin),
+            direction: self.vector(ray.direction),
+            time: ray.time,
+        }
+    }
+
+    /// The tightest box around the transformed `bbox` (Arvo's method: each
+    /// output axis sums the extremes of every input axis).
+    #[must_use]
+    pub fn bounds(&self, bbox: AABB) -> AABB {
+        if bbox.x.is_empty() || bbox.y.is_empty() || bbox.z.is_empty() {
+            return AABB::EMPTY;
+        }
+        let extent = |row: Vec3, offset: f64| {
+            let terms = [(row.x, bbox.x), (row.y, bbox.y), (row.z, bbox.z)];
+            terms.into_iter().fold(interval(offset, offset), |acc, (w, input)| {
+                // Skipping zero weights keeps 0 × ∞ from turning into NaN.
+                if w == 0.0 {
+                    return acc;
+                }
+                let (a, b) = (w * input.min, w * input.max);
+                Interval::new(acc.min + a.min(b), acc.max + a.max(b))
+            })
+        };
+        let ([r0, r1, r2], o) = (self.m.rows, self.m.offset);
+        AABB::new(extent(r0, o.x), extent(r1, o.y), extent(r2, o.z))
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self { Self::IDENTITY }
+}
+
+/// Composition: `a * b` applies `b` first.
+impl Mul for Transform {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self { m: self.m.compose(&rhs.m), inv: rhs.inv.compose(&self.inv) }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Quaternion
+// ---------------------------------------------------------------------------
+
+/// A rotation stored as a unit quaternion `w + xi + yj + zk`.
+///
+/// Unlike matrices, quaternions interpolate cleanly: [`Quaternion::slerp`]
+/// turns at a constant rate along the shortest arc.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quaternion {
+    pub const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 0.0);
+
+    #[inline]
+    #[must_use]
+    pub const fn new(w: f64, x: f64, y: f64, z: f64) -> Self { Self { w, x, y, z } }
+
+    /// The same rotation as [`Transform::rotate`] with these arguments.
+    #[must_use]
+    pub fn from_axis_angle(axis: Vec3, degrees: f64) -> Self {
+        let (sin, cos) = (0.5 * degrees.to_radians()).sin_cos();
+        let v = sin * axis.unit();
+        Self::new(cos, v.x, v.y, v.z)
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn dot(self, rhs: Self) -> f64 {
+        self.w * rhs.w + self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    #[must_use]
+    pub fn normalize(self) -> Self { self.scale(1.0 / self.dot(self).sqrt()) }
+
+    /// The angle in radians of the rotation that takes `self` to `rhs`.
+    #[must_use]
+    pub fn angle_to(self, rhs: Self) -> f64 { 2.0 * self.dot(rhs).abs().min(1.0).acos() }
+
+    /// Spherical linear interpolation along the shorter arc, at a constant
+    /// angular rate in `t`.
+    #[must_use]
+    pub fn slerp(self, rhs: Self, t: f64) -> Self {
+        // `q` and `-q` are the same rotation; pick the one on this side.
+        let cos = self.dot(rhs);
+        let (rhs, cos) = if cos < 0.0 { (rhs.scale(-1.0), -cos) } else { (rhs, cos) };
+
+        // Nearly equal rotations: sin θ ≈ 0, so fall back to a plain lerp.
+        if cos > 0.9995 {
+            return self.scale(1.0 - t).add(rhs.scale(t)).normalize();
+        }
+        let theta = cos.acos();
+        let sin = theta.sin();
+        self.scale(((1.0 - t) * theta).sin() / sin).add(rhs.scale((t * theta).sin() / sin))
+    }
+
+    const fn scale(self, s: f64) -> Self {
+        Self::new(s * self.w, s * self.x, s * self.y, s * self.z)
+    }
+
+    const fn add(self, rhs: Self) -> Self {
+        Self::new(self.w + rhs.w, self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self { Self::IDENTITY }
+}
+
+/// Hamilton product: `a * b` rotates by `b` first.
+impl Mul for Quaternion {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let (a, b) = (self, rhs);
+        Self::new(
+            a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+            a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        )
+    }
+}
+
+/// The rotation matrix of a unit quaternion.
+impl From<Quaternion> for Transform {
+    fn from(q: Quaternion) -> Self {
+        let Quaternion { w, x, y, z } = q;
+        let m = Affine::linear([
+            Vec3::new(1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y)),
+            Vec3::new(2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x)),
+            Vec3::new(2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y)),
+        ]);
+        Self { m, inv: Affine::linear(m.transpose()) }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use shared::TOLERANCE;
+
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < TOLERANCE, "{a} != {b}");
+    }
+
+    #[test]
+    fn scenario_points_vectors_and_inverse() {
+        let t = Transform::translate(vec3(1, 2, 3))
+            * Transform::rotate(Vec3::Y, 90.0)
+            * Transform::scale(vec3(2, 2, 2));
+        // Scale, then a quarter turn about +y takes +x to −z, then translate.
+        assert_close(t.point(Vec3::X), point3(1, 2, 1));
+        assert_close(t.vector(Vec3::X), vec3(0, 0, -2));
+
+        let p = point3(0.3, -1.7, 4.2);
+        assert_close(t.inverse().point(t.point(p)), p);
+        assert_close((t * t.inverse()).point(p), p);
+        assert_close(
+            Transform::scale(vec3(2, 2, 2)).then(&Transform::translate(Vec3::X)).point(Vec3::ONE),
+            point3(3, 2, 2),
+        );
+    }
+
+    #[test]
+    fn scenario_normals_stay_perpendicular() {
+        let t = Transform::scale(vec3(4, 1, 1)) * Transform::rotate(vec3(1, 1, 0), 30.0);
+        // The plane x + y = 0 has normal (1, 1, 0) and contains (1, −1, 0).
+        let (n, tangent) = (vec3(1, 1, 0), vec3(1, -1, 0));
+        shared::assert_fuzzy_eq!(t.normal(n).dot(t.vector(tangent)), 0.0);
+        // Transforming the normal as an ordinary vector would not be.
+        assert!(t.vector(n).dot(t.vector(tangent)).abs() > TOLERANCE);
+
+        // The normal matrix's first column is the image of +x.
+        let [r0, r1, r2] = t.normal_matrix();
+        assert_close(Vec3::new(r0[0], r1[0], r2[0]), t.normal(Vec3::X));
+    }
+
+    #[test]
+    fn scenario_from_matrix() {
+        let t = Transform::translate(vec3(1, 2, 3)) * Transform::rotate(Vec3::Z, 45.0);
+        assert_eq!(Transform::from_matrix(t.matrix()).map(|m| m.matrix()), Some(t.matrix()));
+
+        let singular = Transform::scale(vec3(1, 1, 0)).matrix();
+        assert_eq!(Transform::from_matrix(singular), None);
+        let mut projective = Transform::IDENTITY.matrix();
+        projective[3] = [0.0, 0.0, 1.0, 0.0];
+        assert_eq!(Transform::from_matrix(projective), None);
+    }
+
+    #[test]
+    fn scenario_bounds() {
+        let unit = AABB::from((Point3::ZERO, Point3::ONE));
+        let bbox = Transform::rotate(Vec3::Z, 45.0).bounds(unit);
+        let half = 0.5_f64.sqrt();
+        shared::assert_fuzzy_eq!(bbox.x.min, -half);
+        shared::assert_fuzzy_eq!(bbox.x.max, half);
+        shared::assert_fuzzy_eq!(bbox.y.max, 2.0 * half);
+        shared::assert_fuzzy_eq!(bbox.z.max, 1.0);
+
+        let slab = AABB::new(Interval::UNIVERSE, interval(0, 1), Interval::UNIVERSE);
+        let moved = Transform::translate(vec3(0, 5, 0)).bounds(slab);
+        assert_eq!((moved.y.min, moved.y.max), (5.0, 6.0));
+        assert!(moved.x.is_universe());
+        assert!(Transform::IDENTITY.bounds(AABB::EMPTY).x.is_empty());
+    }
+
+    #[test]
+    fn scenario_quaternions() {
+        let q = Quaternion::from_axis_angle(vec3(1, 2, 3), 70.0);
+        let p = point3(0.5, -2.0, 1.5);
+        assert_close(Transform::from(q).point(p), Transform::rotate(vec3(1, 2, 3), 70.0).point(p));
+
+        let a = Quaternion::from_axis_angle(Vec3::Z, 10.0);
+        let b = Quaternion::from_axis_angle(Vec3::Z, 170.0);
+        let mid = Transform::from(a.slerp(b, 0.5));
+        assert_close(mid.vector(Vec3::X), Transform::rotate(Vec3::Z, 90.0).vector(Vec3::X));
+        shared::assert_fuzzy_eq!(a.angle_to(b), 160.0_f64.to_radians());
+        // The shorter arc is taken even when the signs disagree.
+        let flipped = Quaternion::new(-b.w, -b.x, -b.y, -b.z);
+        assert_close(Transform::from(a.slerp(flipped, 0.5)).vector(Vec3::X), Vec3::Y);
+        assert_close(
+            Transform::from(b * a).vector(Vec3::X),
+            Transform::rotate(Vec3::Z, 180.0).vector(Vec3::X),
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
-use crate::prelude::{AABB, Interval, Material, Point3, Ray, Vec3, interval};
+use rand::prelude::Rng;
+use shared::random_index;
+
+use crate::prelude::{AABB, Color3, Interval, Material, Point3, Ray, Vec3, interval};
 
 /// All information about a ray–surface intersection.
 pub struct HitRecord {
@@ -10,6 +13,13 @@ pub struct HitRecord {
     pub normal: Vec3,
     /// Ray parameter *t* at the intersection.
     pub t: f64,
+    /// Surface coordinate `u` at `p`, for texture lookup.
+    pub u: f64,
+    /// Surface coordinate `v` at `p`, for texture lookup.
+    pub v: f64,
+    /// Colour interpolated from per-vertex colours, for meshes that have
+    /// them.
+    pub color: Option<Color3>,
     /// The material of the intersected surface.
     pub material: Arc<dyn Material>,
     /// `true` if the ray hit the front face of the surface.
@@ -36,6 +46,22 @@ pub trait Hittable: Send + Sync {
 
     /// Returns the closest hit in the interval `t`, or `None`.
     fn hit(&self, ray: &Ray, t: Interval) -> Option<HitRecord>;
+
+    /// Whether [`Hittable::random`] can aim rays at the object, so that it
+    /// can stand in as a light. Cheap enough to ask once per scene; `false`
+    /// by default.
+    fn is_sampleable(&self) -> bool { false }
+
+    /// The density, per unit solid angle, with which [`Hittable::random`]
+    /// picks `direction` from `origin`: zero for directions that miss the
+    /// object, and `None` for objects that are not
+    /// [sampleable](Hittable::is_sampleable).
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3) -> Option<f64> { None }
+
+    /// A random direction from `origin` towards a point on the object, for
+    /// aiming rays at lights. Not necessarily unit length. `None` for
+    /// objects that are not [sampleable](Hittable::is_sampleable).
+    fn random(&self, _rng: &mut dyn Rng, _origin: Point3) -> Option<Vec3> { None }
 }
 
 // ---------------------------------------------------------------------------
@@ -46,9 +72,11 @@ pub trait Hittable: Send + Sync {
 ///
 /// Stores each object behind an `Arc<dyn Hittable>` so that objects can be
 /// cheaply shared between the scene and, e.g., motion-blur or instancing.
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Hittables {
     objects: Vec<Arc<dyn Hittable>>,
+    /// Indices of the objects that can be sampled as lights.
+    sampleable: Vec<usize>,
     bbox: AABB,
 }
 
@@ -60,12 +88,16 @@ impl Hittables {
     #[inline]
     pub fn add(&mut self, object: Arc<dyn Hittable>) {
         self.bbox = AABB::from((self.bbox, object.bounding_box()));
+        if object.is_sampleable() {
+            self.sampleable.push(self.objects.len());
+        }
         self.objects.push(object);
     }
 
     #[inline]
     pub fn clear(&mut self) {
         self.objects.clear();
+        self.sampleable.clear();
         self.bbox = AABB::EMPTY;
     }
 
@@ -95,6 +127,28 @@ impl Hittable for Hittables {
         record
     }
 
+    fn is_sampleable(&self) -> bool { !self.sampleable.is_empty() }
+
+    /// Each object that can be sampled is picked with equal probability, so
+    /// the density is the average of theirs. The others are skipped.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> Option<f64> {
+        let count = u32::try_from(self.sampleable.len()).ok().filter(|&count| count > 0)?;
+        let total: f64 = self
+            .sampleable
+            .iter()
+            .filter_map(|&index| self.objects.get(index)?.pdf_value(origin, direction))
+            .sum();
+        Some(total / f64::from(count))
+    }
+
+    fn random(&self, rng: &mut dyn Rng, origin: Point3) -> Option<Vec3> {
+        if self.sampleable.is_empty() {
+            return None;
+        }
+        let index = *self.sampleable.get(random_index(rng, self.sampleable.len()))?;
+        self.objects.get(index)?.random(rng, origin)
+    }
+
     fn bounding_box(&self) -> AABB { self.bbox } // AABB is Copy — free
 }
 
@@ -107,6 +161,19 @@ impl Hittable for Arc<dyn Hittable> {
 
     #[inline]
     fn bounding_box(&self) -> AABB { self.as_ref().bounding_box() }
+
+    #[inline]
+    fn is_sampleable(&self) -> bool { self.as_ref().is_sampleable() }
+
+    #[inline]
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> Option<f64> {
+        self.as_ref().pdf_value(origin, direction)
+    }
+
+    #[inline]
+    fn random(&self, rng: &mut dyn Rng, origin: Point3) -> Option<Vec3> {
+        self.as_ref().random(rng, origin)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -115,18 +182,15 @@ impl Hittable for Arc<dyn Hittable> {
 
 impl FromIterator<Arc<dyn Hittable>> for Hittables {
     fn from_iter<I: IntoIterator<Item = Arc<dyn Hittable>>>(iter: I) -> Self {
-        Self { objects: iter.into_iter().collect(), bbox: AABB::EMPTY }
+        let mut list = Self::new();
+        iter.into_iter().for_each(|object| list.add(object));
+        list
     }
 }
 
 #[expect(clippy::as_conversions, trivial_casts)]
 impl<T: Hittable + 'static> From<Vec<T>> for Hittables {
-    fn from(v: Vec<T>) -> Self {
-        Self {
-            objects: v.into_iter().map(|h| Arc::new(h) as Arc<dyn Hittable>).collect(),
-            bbox: AABB::EMPTY,
-        }
-    }
+    fn from(v: Vec<T>) -> Self { v.into_iter().map(|h| Arc::new(h) as Arc<dyn Hittable>).collect() }
 }
 
 impl IntoIterator for Hittables {
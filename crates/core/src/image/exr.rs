@@ -0,0 +1,299 @@
+//! Scanline `OpenEXR` encoding.
+//!
+//! Writes a single-part scanline file with 32-bit float `R`, `G` and `B`
+//! channels, either uncompressed or with ZIP compression.
+
+use std::io;
+
+use crate::image::{Image, ImageWriter, narrow, to_usize, zlib};
+use crate::prelude::Color3;
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+/// Format version 2, single-part scanline.
+const VERSION: [u8; 4] = [2, 0, 0, 0];
+/// Pixel type `FLOAT` in a channel list.
+const PIXEL_FLOAT: i32 = 2;
+
+/// Compression applied to each block of scanlines.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExrCompression {
+    /// One scanline per block, stored raw.
+    #[default]
+    None,
+    /// Sixteen scanlines per block, predicted and deflated.
+    Zip,
+}
+
+impl ExrCompression {
+    /// The value of the `compression` header attribute.
+    const fn id(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Zip => 3,
+        }
+    }
+
+    const fn lines_per_block(self) -> usize {
+        match self {
+            Self::None => 1,
+            Self::Zip => 16,
+        }
+    }
+}
+
+/// Writes linear colours as 32-bit float `OpenEXR`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExrWriter {
+    pub compression: ExrCompression,
+}
+
+impl ExrWriter {
+    #[inline]
+    #[must_use]
+    pub const fn new(compression: ExrCompression) -> Self { Self { compression } }
+}
+
+impl ImageWriter for ExrWriter {
+    #[expect(clippy::little_endian_bytes, reason = "`OpenEXR` is little-endian throughout")]
+    fn write(&self, image: &Image, out: &mut dyn io::Write) -> io::Result<()> {
+        let too_large = |_| io::Error::new(io::ErrorKind::InvalidInput, "image too large for EXR");
+        if image.width() == 0 || image.height() == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "cannot write an empty EXR"));
+        }
+        let max_x = i32::try_from(image.width() - 1).map_err(too_large)?;
+        let max_y = i32::try_from(image.height() - 1).map_err(too_large)?;
+
+        let header = header(self.compression, max_x, max_y);
+
+        let width = to_usize(image.width());
+        let lines = self.compression.lines_per_block();
+        let mut blocks = Vec::new();
+        for (i, block) in image.pixels().chunks(width * lines).enumerate() {
+            let mut raw = Vec::with_capacity(block.len() * 12);
+            // Channels are stored in alphabetical order, one plane per line.
+            for row in block.chunks(width) {
+                let channels: [fn(&Color3) -> f64; 3] = [|c| c.b, |c| c.g, |c| c.r];
+                for channel in channels {
+                    raw.extend(row.iter().map(channel).flat_map(|v| narrow(v).to_le_bytes()));
+                }
+            }
+            let data = match self.compression {
+                ExrCompression::None => raw,
+                ExrCompression::Zip => {
+                    let compressed = zlib::compress(&predict(&interleave(&raw)));
+                    if compressed.len() < raw.len() { compressed } else { raw }
+                }
+            };
+            let y = i32::try_from(i * lines).map_err(too_large)?;
+            blocks.push((y, data));
+        }
+
+        // The offset table follows the header, one u64 per block.
+        let mut offset = u64::try_from(header.len() + blocks.len() * 8).map_err(too_large)?;
+        let mut table = Vec::with_capacity(blocks.len() * 8);
+        for block in &blocks {
+            table.extend_from_slice(&offset.to_le_bytes());
+            offset += u64::try_from(8 + block.1.len()).map_err(too_large)?;
+        }
+
+        out.write_all(&header)?;
+        out.write_all(&table)?;
+        for (y, data) in blocks {
+            let len = i32::try_from(data.len()).map_err(too_large)?;
+            out.write_all(&y.to_le_bytes())?;
+            out.write_all(&len.to_le_bytes())?;
+            out.write_all(&data)?;
+        }
+        out.flush()
+    }
+}
+
+#[expect(clippy::little_endian_bytes, reason = "`OpenEXR` is little-endian throughout")]
+fn header(compression: ExrCompression, max_x: i32, max_y: i32) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend_from_slice(&MAGIC);
+    header.extend_from_slice(&VERSION);
+
+    let mut channels = Vec::new();
+    for name in *b"BGR" {
+        channels.extend_from_slice(&[name, 0]);
+        channels.extend_from_slice(&PIXEL_FLOAT.to_le_bytes());
+        // pLinear and three reserved bytes, then x and y sampling.
+        channels.extend_from_slice(&[0; 4]);
+        channels.extend_from_slice(&1_i32.to_le_bytes());
+        channels.extend_from_slice(&1_i32.to_le_bytes());
+    }
+    channels.push(0);
+
+    let window: Vec<u8> = [0, 0, max_x, max_y].iter().flat_map(|v| v.to_le_bytes()).collect();
+    let floats =
+        |values: &[f32]| -> Vec<u8> { values.iter().flat_map(|v| v.to_le_bytes()).collect() };
+
+    attribute(&mut header, "channels", "chlist", &channels);
+    attribute(&mut header, "compression", "compression", &[compression.id()]);
+    attribute(&mut header, "dataWindow", "box2i", &window);
+    attribute(&mut header, "displayWindow", "box2i", &window);
+    attribute(&mut header, "lineOrder", "lineOrder", &[0]); // increasing y
+    attribute(&mut header, "pixelAspectRatio", "float", &floats(&[1.0]));
+    attribute(&mut header, "screenWindowCenter", "v2f", &floats(&[0.0, 0.0]));
+    attribute(&mut header, "screenWindowWidth", "float", &floats(&[1.0]));
+    header.push(0);
+    header
+}
+
+#[expect(clippy::little_endian_bytes, reason = "`OpenEXR` is little-endian throughout")]
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    for s in [name, kind] {
+        header.extend_from_slice(s.as_bytes());
+        header.push(0);
+    }
+    header.extend_from_slice(&u32::try_from(value.len()).unwrap_or(0).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+/// Splits bytes into even- and odd-indexed halves, as the ZIP codec does
+/// before prediction.
+fn interleave(raw: &[u8]) -> Vec<u8> {
+    raw.iter().step_by(2).chain(raw.iter().skip(1).step_by(2)).copied().collect()
+}
+
+/// Replaces each byte with its difference from the previous one, offset by
+/// 128.
+fn predict(data: &[u8]) -> Vec<u8> {
+    let previous = core::iter::once(0).chain(data.iter().copied());
+    data.iter()
+        .zip(previous)
+        .enumerate()
+        .map(|(i, (&d, p))| if i == 0 { d } else { d.wrapping_sub(p).wrapping_add(128) })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+#[expect(clippy::little_endian_bytes, reason = "`OpenEXR` is little-endian throughout")]
+mod tests {
+    use super::*;
+    use crate::prelude::color;
+
+    fn read_u64(bytes: &[u8], at: usize) -> usize {
+        let field = bytes.get(at..at + 8).unwrap().try_into().unwrap();
+        usize::try_from(u64::from_le_bytes(field)).unwrap()
+    }
+
+    fn read_i32(bytes: &[u8], at: usize) -> i32 {
+        i32::from_le_bytes(bytes.get(at..at + 4).unwrap().try_into().unwrap())
+    }
+
+    /// The stored bytes of block `block` of `file`.
+    fn block_data(file: &[u8], table: usize, block: usize) -> &[u8] {
+        let offset = read_u64(file, table + block * 8);
+        let len = usize::try_from(read_i32(file, offset + 4)).unwrap();
+        file.get(offset + 8..offset + 8 + len).unwrap()
+    }
+
+    /// Inverts `predict(&interleave(raw))`.
+    fn unpredict(coded: &[u8]) -> Vec<u8> {
+        let mut undone: Vec<u8> = Vec::with_capacity(coded.len());
+        for &c in coded {
+            let next = undone.last().map_or(c, |&p| c.wrapping_add(p).wrapping_sub(128));
+            undone.push(next);
+        }
+        let (even, odd) = undone.split_at(coded.len().div_ceil(2));
+        let mut restored = Vec::with_capacity(coded.len());
+        for (i, &e) in even.iter().enumerate() {
+            restored.push(e);
+            restored.extend(odd.get(i));
+        }
+        restored
+    }
+
+    fn floats(bytes: &[u8]) -> Vec<f32> {
+        bytes.chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect()
+    }
+
+    /// The B, G and R planes of each of `rows` of `image`, as stored.
+    fn planes(image: &Image, rows: core::ops::Range<u32>) -> Vec<f32> {
+        let mut planes = Vec::new();
+        for y in rows {
+            let row: Vec<_> = (0..image.width()).map(|x| image.get(x, y).unwrap()).collect();
+            planes.extend(row.iter().map(|c| narrow(c.b)));
+            planes.extend(row.iter().map(|c| narrow(c.g)));
+            planes.extend(row.iter().map(|c| narrow(c.r)));
+        }
+        planes
+    }
+
+    #[test]
+    fn scenario_offset_table_points_at_blocks() {
+        let mut image = Image::new(5, 20);
+        image.set(2, 17, color(3.0, 2.0, 1.0));
+
+        for (compression, blocks) in [(ExrCompression::None, 20), (ExrCompression::Zip, 2)] {
+            let mut file = Vec::new();
+            ExrWriter::new(compression).write(&image, &mut file).unwrap();
+            assert_eq!(file.get(..4), Some(&MAGIC[..]));
+
+            let table = header(compression, 4, 19).len();
+            let lines = i32::try_from(compression.lines_per_block()).unwrap();
+            for block in 0..blocks {
+                let offset = read_u64(&file, table + block * 8);
+                assert_eq!(read_i32(&file, offset), i32::try_from(block).unwrap() * lines);
+            }
+        }
+    }
+
+    #[test]
+    fn scenario_uncompressed_planes_are_bgr() {
+        let mut image = Image::new(1, 1);
+        image.set(0, 0, color(1.0, 2.0, 4.0));
+
+        let mut file = Vec::new();
+        ExrWriter::default().write(&image, &mut file).unwrap();
+        let (_, pixel) = file.split_at(file.len() - 12);
+        assert_eq!(floats(pixel), [4.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn scenario_zip_blocks_decode_to_the_pixels() {
+        // A gradient compresses well; the second block is a partial one.
+        let mut image = Image::new(7, 20);
+        for y in 0..20 {
+            for x in 0..7 {
+                image.set(x, y, color(f64::from(x) * 0.25, f64::from(y) * 0.5, 1.0));
+            }
+        }
+        let mut file = Vec::new();
+        ExrWriter::new(ExrCompression::Zip).write(&image, &mut file).unwrap();
+        let table = header(ExrCompression::Zip, 6, 19).len();
+
+        for (block, rows) in [(0, 0..16), (1, 16..20)] {
+            let expected = planes(&image, rows);
+            let raw_len = expected.len() * 4;
+            let data = block_data(&file, table, block);
+            assert!(data.len() < raw_len, "block {block} was stored raw");
+            let raw = unpredict(&zlib::decompress(data, raw_len).unwrap());
+            assert_eq!(floats(&raw), expected);
+        }
+    }
+
+    #[test]
+    fn scenario_zip_stores_raw_when_not_smaller() {
+        // A few noisy pixels: deflate can't beat the raw bytes.
+        let pixels =
+            (0..3).map(|i| f64::from(i) * 12.345).map(|v| color(v.sin(), v.cos(), v.tan()));
+        let image = Image::from_pixels(3, 1, pixels.collect()).unwrap();
+        let mut file = Vec::new();
+        ExrWriter::new(ExrCompression::Zip).write(&image, &mut file).unwrap();
+        let table = header(ExrCompression::Zip, 2, 0).len();
+        assert_eq!(floats(block_data(&file, table, 0)), planes(&image, 0..1));
+    }
+
+    #[test]
+    fn scenario_predictor_inverts() {
+        let raw: Vec<u8> = (0..=255).chain((0..50).rev()).collect();
+        assert_eq!(unpredict(&predict(&interleave(&raw))), raw);
+    }
+}
@@ -0,0 +1,327 @@
+//! Radiance RGBE (`.hdr`) encoding and decoding.
+//!
+//! Each pixel is three 8-bit mantissas sharing one exponent byte. Scanlines
+//! are written with the "new-style" run-length encoding, which stores each of
+//! the four byte planes separately.
+
+use std::io;
+
+use crate::image::{Image, ImageWriter, invalid, to_usize};
+use crate::prelude::{Color3, color};
+
+const FORMAT: &str = "32-bit_rle_rgbe";
+/// Scanlines outside this width range cannot use run-length encoding.
+const RLE_WIDTHS: core::ops::RangeInclusive<usize> = 8..=0x7fff;
+/// Longest run or literal span a single count byte can describe.
+const MAX_SPAN: usize = 127;
+/// Shortest run worth encoding as a run rather than literals.
+const MIN_RUN: usize = 4;
+
+/// Writes linear colours as run-length encoded Radiance RGBE.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HdrWriter;
+
+impl ImageWriter for HdrWriter {
+    fn write(&self, image: &Image, out: &mut dyn io::Write) -> io::Result<()> {
+        write!(out, "#?RADIANCE\nFORMAT={FORMAT}\n\n-Y {} +X {}\n", image.height(), image.width())?;
+
+        let mut bytes = Vec::new();
+        for row in image.rows().take(to_usize(image.height())) {
+            let rgbe: Vec<[u8; 4]> = row.iter().map(|&c| to_rgbe(c)).collect();
+            if RLE_WIDTHS.contains(&rgbe.len()) {
+                let [hi, lo] = u16::try_from(rgbe.len()).unwrap_or(0).to_be_bytes();
+                bytes.extend_from_slice(&[2, 2, hi, lo]);
+                for channel in 0..4 {
+                    let plane: Vec<u8> =
+                        rgbe.iter().map(|px| px.get(channel).copied().unwrap_or(0)).collect();
+                    encode_plane(&plane, &mut bytes);
+                }
+            } else {
+                bytes.extend(rgbe.iter().flatten());
+            }
+        }
+        out.write_all(&bytes)?;
+        out.flush()
+    }
+}
+
+/// Appends one byte plane as a sequence of runs and literal spans.
+fn encode_plane(plane: &[u8], out: &mut Vec<u8>) {
+    let mut pos = 0;
+    while pos < plane.len() {
+        // Find the next run long enough to be worth encoding.
+        let mut run_start = pos;
+        let mut run_len = 0;
+        while run_start < plane.len() {
+            run_len = run_length(plane, run_start);
+            if run_len >= MIN_RUN {
+                break;
+            }
+            run_start += run_len;
+        }
+        if run_len < MIN_RUN {
+            run_start = plane.len();
+        }
+
+        // Literals up to the run.
+        for span in plane.get(pos..run_start).unwrap_or_default().chunks(MAX_SPAN) {
+            out.push(u8::try_from(span.len()).unwrap_or(0));
+            out.extend_from_slice(span);
+        }
+        pos = run_start;
+
+        if run_len >= MIN_RUN && pos < plane.len() {
+            let value = plane.get(pos).copied().unwrap_or(0);
+            out.extend_from_slice(&[0x80 + u8::try_from(run_len).unwrap_or(0), value]);
+            pos += run_len;
+        }
+    }
+}
+
+/// Number of bytes equal to `plane[start]` from `start`, capped at
+/// [`MAX_SPAN`].
+fn run_length(plane: &[u8], start: usize) -> usize {
+    let rest = plane.get(start..).unwrap_or_default();
+    let first = rest.first();
+    rest.iter().take(MAX_SPAN).take_while(|&b| Some(b) == first).count()
+}
+
+/// Reads a Radiance RGBE file with the standard `-Y height +X width`
+/// orientation, flat or with new-style run-length encoding.
+pub fn read_hdr(input: &mut dyn io::Read) -> io::Result<Image> {
+    let mut data = Vec::new();
+    input.read_to_end(&mut data)?;
+    let mut cursor = Cursor { data: &data, pos: 0 };
+
+    let magic = cursor.line()?;
+    if !magic.starts_with(b"#?") {
+        return Err(invalid("not a Radiance file"));
+    }
+    loop {
+        let line = cursor.line()?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix(b"FORMAT=")
+            && format != FORMAT.as_bytes()
+        {
+            let format = String::from_utf8_lossy(format);
+            return Err(invalid(&format!("unsupported format {format}")));
+        }
+    }
+
+    let resolution = String::from_utf8_lossy(cursor.line()?).into_owned();
+    let (height, width) = match *resolution.split_whitespace().collect::<Vec<_>>() {
+        ["-Y", h, "+X", w] => (h.parse::<u32>().ok(), w.parse::<u32>().ok()),
+        _ => (None, None),
+    };
+    let (Some(height), Some(width)) = (height, width) else {
+        return Err(invalid(&format!("unsupported resolution line '{resolution}'")));
+    };
+    if width == 0 || height == 0 {
+        return Err(invalid("empty Radiance image"));
+    }
+
+    // The resolution line is untrusted, so nothing is sized from it up front:
+    // `pixels` grows as scanlines are actually read, and the RLE planes are
+    // at most 4 × 0x7fff bytes.
+    let w = to_usize(width);
+    let row_bytes = w.checked_mul(4).ok_or_else(|| invalid("image too wide"))?;
+    let mut pixels = Vec::new();
+    let mut planes = if RLE_WIDTHS.contains(&w) { vec![0; row_bytes] } else { Vec::new() };
+    for _ in 0..height {
+        let rle = RLE_WIDTHS.contains(&w)
+            && matches!(*cursor.peek(4), [2, 2, hi, lo] if hi < 0x80 && usize::from(u16::from_be_bytes([hi, lo])) == w);
+        if rle {
+            cursor.take(4)?;
+            for plane in planes.chunks_mut(w) {
+                decode_plane(&mut cursor, plane)?;
+            }
+            pixels.extend((0..w).map(|x| {
+                let at = |channel: usize| planes.get(channel * w + x).copied().unwrap_or(0);
+                from_rgbe([at(0), at(1), at(2), at(3)])
+            }));
+        } else {
+            let row = cursor.take(row_bytes)?;
+            pixels
+                .extend(row.chunks_exact(4).map(|px| from_rgbe(px.try_into().unwrap_or_default())));
+        }
+    }
+
+    Image::from_pixels(width, height, pixels).ok_or_else(|| invalid("pixel count mismatch"))
+}
+
+fn decode_plane(cursor: &mut Cursor<'_>, plane: &mut [u8]) -> io::Result<()> {
+    let mut pos = 0;
+    while pos < plane.len() {
+        let count = cursor.byte()?;
+        let (len, run) = if count > 0x80 {
+            (usize::from(count - 0x80), true)
+        } else {
+            (usize::from(count), false)
+        };
+        let span = plane
+            .get_mut(pos..pos + len)
+            .filter(|_| len > 0)
+            .ok_or_else(|| invalid("bad scanline run length"))?;
+        if run {
+            span.fill(cursor.byte()?);
+        } else {
+            span.copy_from_slice(cursor.take(len)?);
+        }
+        pos += len;
+    }
+    Ok(())
+}
+
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let bytes =
+            self.data.get(self.pos..self.pos + n).ok_or_else(|| invalid("truncated file"))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> io::Result<u8> {
+        self.take(1)?.first().copied().ok_or_else(|| invalid("truncated file"))
+    }
+
+    fn peek(&self, n: usize) -> &'a [u8] {
+        self.data.get(self.pos..self.pos + n).unwrap_or_default()
+    }
+
+    /// The next `\n`-terminated line, without the terminator.
+    fn line(&mut self) -> io::Result<&'a [u8]> {
+        let rest = self.data.get(self.pos..).unwrap_or_default();
+        let len =
+            rest.iter().position(|&b| b == b'\n').ok_or_else(|| invalid("truncated header"))?;
+        self.pos += len + 1;
+        Ok(rest.get(..len).unwrap_or_default())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Shared-exponent conversion
+// ---------------------------------------------------------------------------
+
+/// Below this the pixel is stored as black.
+const RGBE_MIN: f64 = 1e-32;
+/// Above this the exponent byte would overflow.
+const RGBE_MAX: f64 = 1e38;
+
+fn to_rgbe(c: Color3) -> [u8; 4] {
+    let [r, g, b] = [c.r, c.g, c.b].map(|v| if v > 0.0 { v.min(RGBE_MAX) } else { 0.0 });
+    let max = r.max(g).max(b);
+    if max < RGBE_MIN {
+        return [0; 4];
+    }
+    let (mantissa, exponent) = frexp(max);
+    let scale = mantissa * 256.0 / max;
+    let exponent = u8::try_from(exponent + 128).unwrap_or(0);
+    [mantissa_byte(r * scale), mantissa_byte(g * scale), mantissa_byte(b * scale), exponent]
+}
+
+fn from_rgbe(rgbe: [u8; 4]) -> Color3 {
+    let [r, g, b, e] = rgbe;
+    if e == 0 {
+        return Color3::BLACK;
+    }
+    // Sample at the centre of each mantissa bucket.
+    let f = 2_f64.powi(i32::from(e) - (128 + 8));
+    color((f64::from(r) + 0.5) * f, (f64::from(g) + 0.5) * f, (f64::from(b) + 0.5) * f)
+}
+
+/// Splits a positive, normal `v` into `mantissa × 2^exponent` with the
+/// mantissa in `[0.5, 1)`.
+fn frexp(v: f64) -> (f64, i32) {
+    const EXPONENT_MASK: u64 = 0x7ff << 52;
+    let bits = v.to_bits();
+    let biased = i32::try_from((bits & EXPONENT_MASK) >> 52).unwrap_or(0);
+    (f64::from_bits((bits & !EXPONENT_MASK) | (1022 << 52)), biased - 1022)
+}
+
+#[expect(clippy::as_conversions, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+const fn mantissa_byte(v: f64) -> u8 { v as u8 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative_error(a: f64, b: f64) -> f64 { (a - b).abs() / b.abs().max(1e-9) }
+
+    #[test]
+    fn scenario_rgbe_precision() {
+        for c in [color(1.0, 0.5, 0.25), color(123.0, 4.5, 0.0), color(1e-3, 2e-3, 3e-3)] {
+            let decoded = from_rgbe(to_rgbe(c));
+            let max = c.r.max(c.g).max(c.b);
+            for (a, b) in [(decoded.r, c.r), (decoded.g, c.g), (decoded.b, c.b)] {
+                // One mantissa step relative to the brightest channel.
+                assert!((a - b).abs() <= max / 128.0, "{a} vs {b}");
+            }
+        }
+        assert_eq!(to_rgbe(Color3::BLACK), [0; 4]);
+        assert_eq!(to_rgbe(color(-1.0, 0.0, 0.0)), [0; 4]);
+    }
+
+    #[test]
+    fn scenario_round_trip_rle_and_flat() {
+        for width in [3, 40] {
+            let mut image = Image::new(width, 3);
+            for x in 0..width {
+                image.set(x, 1, color(f64::from(x) * 10.0, 0.5, 2.0));
+            }
+            image.set(0, 2, color(5000.0, 0.01, 1.0));
+
+            let mut out = Vec::new();
+            HdrWriter.write(&image, &mut out).unwrap();
+            let decoded = read_hdr(&mut out.as_slice()).unwrap();
+
+            assert_eq!((decoded.width(), decoded.height()), (width, 3));
+            for (a, b) in decoded.pixels().iter().zip(image.pixels()) {
+                let max = b.r.max(b.g).max(b.b);
+                assert!(relative_error(a.r.max(a.g).max(a.b), max) < 0.01 || max == 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn scenario_runs_compress_flat_rows() {
+        let mut out = Vec::new();
+        HdrWriter.write(&Image::new(100, 1), &mut out).unwrap();
+        let header_len = out.iter().rposition(|&b| b == b'\n').unwrap() + 1;
+        // Scanline marker plus one two-byte run per plane.
+        assert_eq!(out.len() - header_len, 4 + 4 * 2);
+    }
+
+    #[test]
+    fn scenario_oversized_resolution_is_an_error() {
+        let data = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 100000 +X 100000\n\0\0\0\0";
+        let err = read_hdr(&mut data.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn scenario_empty_resolution_is_an_error() {
+        // Zero-width scanlines would otherwise be "read" four billion times.
+        for resolution in ["-Y 4294967295 +X 0", "-Y 0 +X 4294967295"] {
+            let data = format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n{resolution}\n");
+            let err = read_hdr(&mut data.as_bytes()).unwrap_err();
+            assert_eq!(err.to_string(), "empty Radiance image");
+        }
+    }
+
+    #[test]
+    fn scenario_rejects_xyze() {
+        let data = b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n\0\0\0\0";
+        assert!(read_hdr(&mut data.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+//! In-memory images and the codecs that read and write them.
+//!
+//! The camera renders into an [`Image`] of linear [`Color3`] radiance. An
+//! [`ImageWriter`] then encodes it to any [`io::Write`] sink, so the same
+//! render can go to stdout, a file, or a buffer in a test.
+
+mod exr;
+mod hdr;
+mod pfm;
+mod png;
+mod ppm;
+mod zlib;
+
+use core::fmt;
+use core::str::FromStr;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+pub use self::exr::{ExrCompression, ExrWriter};
+pub use self::hdr::{HdrWriter, read_hdr};
+pub use self::pfm::{PfmWriter, read_pfm};
+pub use self::png::{PngBitDepth, PngWriter, read_png};
+pub use self::ppm::{PpmFormat, PpmWriter, read_ppm};
+use crate::prelude::{Color3, ToneMapper, TransferFunction};
+
+/// A `width × height` grid of linear colours, stored row-major from the top
+/// row down.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color3>,
+}
+
+impl Image {
+    /// An all-black image.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height, pixels: vec![Color3::BLACK; to_usize(width) * to_usize(height)] }
+    }
+
+    /// Wraps a row-major pixel buffer. Returns `None` if `pixels` does not
+    /// hold exactly `width × height` entries.
+    #[must_use]
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<Color3>) -> Option<Self> {
+        (pixels.len() == to_usize(width) * to_usize(height)).then_some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn width(&self) -> u32 { self.width }
+
+    #[inline]
+    #[must_use]
+    pub const fn height(&self) -> u32 { self.height }
+
+    /// All pixels, row-major from the top-left corner.
+    #[inline]
+    #[must_use]
+    pub fn pixels(&self) -> &[Color3] { &self.pixels }
+
+    #[inline]
+    #[must_use]
+    pub fn pixels_mut(&mut self) -> &mut [Color3] { &mut self.pixels }
+
+    /// The pixel at column `x`, row `y`, or `None` outside the image.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, x: u32, y: u32) -> Option<Color3> {
+        self.index(x, y).and_then(|i| self.pixels.get(i)).copied()
+    }
+
+    /// Overwrites the pixel at column `x`, row `y`. Out-of-range coordinates
+    /// are ignored.
+    #[inline]
+    pub fn set(&mut self, x: u32, y: u32, color: Color3) {
+        if let Some(pixel) = self.index(x, y).and_then(|i| self.pixels.get_mut(i)) {
+            *pixel = color;
+        }
+    }
+
+    /// Iterates over the rows, top to bottom.
+    #[must_use]
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[Color3]> {
+        self.pixels.chunks(to_usize(self.width).max(1))
+    }
+
+    /// Applies `mapper` to every pixel in place.
+    pub fn tone_map(&mut self, mapper: &dyn ToneMapper) {
+        for pixel in &mut self.pixels {
+            *pixel = mapper.map(*pixel);
+        }
+    }
+
+    /// Reads an image from `path`, choosing the decoder from its extension
+    /// (`.ppm`, `.png`, `.pfm` or `.hdr`).
+    ///
+    /// 8- and 16-bit formats are decoded to linear with `transfer`; the
+    /// floating-point formats are linear already and ignore it.
+    pub fn open(path: impl AsRef<Path>, transfer: TransferFunction) -> Result<Self, ImageError> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+        let extension = extension.to_ascii_lowercase();
+        if !matches!(&*extension, "ppm" | "png" | "pfm" | "hdr") {
+            return Err(ImageError::UnsupportedFormat(path.to_path_buf()));
+        }
+
+        let with_path = |source: io::Error| match source.kind() {
+            io::ErrorKind::NotFound => ImageError::NotFound(path.to_path_buf()),
+            _ => ImageError::Io { path: path.to_path_buf(), source },
+        };
+        let mut input = BufReader::new(File::open(path).map_err(with_path)?);
+        match &*extension {
+            "ppm" => read_ppm(&mut input, transfer),
+            "png" => read_png(&mut input, transfer),
+            "pfm" => read_pfm(&mut input),
+            _ => read_hdr(&mut input),
+        }
+        .map_err(with_path)
+    }
+
+    /// Writes the image to `path`, choosing the format from its extension
+    /// (`.ppm`, `.png`, `.pfm`, `.hdr` or `.exr`).
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+        let writer = writer_for_extension(&extension.to_ascii_lowercase()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("no image writer for '{}'", path.display()),
+            )
+        })?;
+
+        let mut out = BufWriter::new(File::create(path)?);
+        writer.write(self, &mut out)
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height)
+            .then(|| to_usize(y) * to_usize(self.width) + to_usize(x))
+    }
+}
+
+/// Why an image file could not be loaded.
+#[derive(Debug)]
+pub enum ImageError {
+    /// Nothing exists at the path.
+    NotFound(PathBuf),
+    /// The extension does not name a format this crate can read.
+    UnsupportedFormat(PathBuf),
+    /// The file could not be read, or is not valid for its format.
+    Io { path: PathBuf, source: io::Error },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::NotFound(ref path) => write!(f, "image '{}' not found", path.display()),
+            Self::UnsupportedFormat(ref path) => {
+                write!(f, "no image reader for '{}'", path.display())
+            }
+            Self::Io { ref path, ref source } => {
+                write!(f, "cannot load image '{}': {source}", path.display())
+            }
+        }
+    }
+}
+
+impl core::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match *self {
+            Self::Io { ref source, .. } => Some(source),
+            Self::NotFound(_) | Self::UnsupportedFormat(_) => None,
+        }
+    }
+}
+
+/// Encodes an [`Image`] in some file format.
+pub trait ImageWriter {
+    fn write(&self, image: &Image, out: &mut dyn io::Write) -> io::Result<()>;
+}
+
+fn writer_for_extension(extension: &str) -> Option<Box<dyn ImageWriter>> {
+    match extension {
+        "ppm" => Some(Box::new(PpmWriter::default())),
+        "png" => Some(Box::new(PngWriter::default())),
+        "pfm" => Some(Box::new(PfmWriter)),
+        "hdr" => Some(Box::new(HdrWriter)),
+        "exr" => Some(Box::new(ExrWriter::default())),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Decoding helpers
+// ---------------------------------------------------------------------------
+
+fn invalid(message: &str) -> io::Error { io::Error::new(io::ErrorKind::InvalidData, message) }
+
+/// Whitespace-separated tokens of a text header, skipping `#` comments.
+struct Tokens<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    const fn new(data: &'a [u8]) -> Self { Self { data, pos: 0 } }
+
+    fn next_token(&mut self) -> Option<&'a [u8]> {
+        loop {
+            let rest = self.data.get(self.pos..)?;
+            let skip = rest.iter().take_while(|b| b.is_ascii_whitespace()).count();
+            self.pos += skip;
+            if self.data.get(self.pos) == Some(&b'#') {
+                let line = self.data.get(self.pos..)?;
+                self.pos += line.iter().take_while(|&&b| b != b'\n').count();
+                continue;
+            }
+            let rest = self.data.get(self.pos..)?;
+            let len = rest.iter().take_while(|b| !b.is_ascii_whitespace()).count();
+            self.pos += len;
+            return (len > 0).then(|| rest.get(..len)).flatten();
+        }
+    }
+
+    fn parse<T: FromStr>(&mut self, what: &str) -> io::Result<T> {
+        let token = self.next_token().ok_or_else(|| invalid(&format!("missing {what}")))?;
+        core::str::from_utf8(token)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| invalid(&format!("invalid {what}: {}", String::from_utf8_lossy(token))))
+    }
+}
+
+/// Rounds to the nearest `f32`, as stored by the floating-point formats.
+#[expect(clippy::as_conversions, clippy::cast_possible_truncation)]
+const fn narrow(v: f64) -> f32 { v as f32 }
+
+#[expect(clippy::as_conversions)]
+const fn to_usize(n: u32) -> usize { n as usize }
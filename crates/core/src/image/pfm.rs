@@ -0,0 +1,121 @@
+//! Portable Float Map (PFM) encoding and decoding.
+//!
+//! PFM is the floating-point sibling of PPM: a short text header followed by
+//! raw 32-bit floats, stored bottom row first.
+
+use std::io;
+
+use crate::image::{Image, ImageWriter, Tokens, invalid, narrow};
+use crate::prelude::{Color3, color};
+
+/// Writes linear colours as little-endian RGB PFM, with no clamping or
+/// gamma.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PfmWriter;
+
+impl ImageWriter for PfmWriter {
+    #[expect(clippy::little_endian_bytes, reason = "a negative scale marks little-endian data")]
+    fn write(&self, image: &Image, out: &mut dyn io::Write) -> io::Result<()> {
+        writeln!(out, "PF\n{} {}\n-1.0", image.width(), image.height())?;
+
+        let bytes: Vec<u8> = image
+            .rows()
+            .rev()
+            .flatten()
+            .flat_map(|c| [c.r, c.g, c.b])
+            .flat_map(|v| narrow(v).to_le_bytes())
+            .collect();
+        out.write_all(&bytes)?;
+        out.flush()
+    }
+}
+
+/// Reads an RGB (`PF`) or greyscale (`Pf`) PFM file.
+///
+/// Samples are multiplied by the magnitude of the header's scale factor, as
+/// most readers do.
+pub fn read_pfm(input: &mut dyn io::Read) -> io::Result<Image> {
+    let mut data = Vec::new();
+    input.read_to_end(&mut data)?;
+
+    let mut header = Tokens::new(&data);
+    let channels = match header.next_token() {
+        Some(b"PF") => 3,
+        Some(b"Pf") => 1,
+        _ => return Err(invalid("not a PF or Pf PFM file")),
+    };
+    let width: u32 = header.parse("width")?;
+    let height: u32 = header.parse("height")?;
+    let scale: f64 = header.parse("scale")?;
+    if scale == 0.0 || !scale.is_finite() {
+        return Err(invalid(&format!("invalid scale {scale}")));
+    }
+
+    let raster = data.get(header.pos + 1..).unwrap_or_default();
+    let little_endian = scale < 0.0;
+    let samples: Vec<f64> = raster
+        .chunks_exact(4)
+        .map(|bytes| {
+            let bytes = bytes.try_into().unwrap_or_default();
+            let value = if little_endian { from_le(bytes) } else { f32::from_be_bytes(bytes) };
+            f64::from(value) * scale.abs()
+        })
+        .collect();
+
+    let row_len = usize::try_from(width).unwrap_or(usize::MAX).saturating_mul(channels);
+    let expected = row_len.saturating_mul(usize::try_from(height).unwrap_or(usize::MAX));
+    if samples.len() < expected {
+        return Err(invalid(&format!("expected {expected} samples, found {}", samples.len())));
+    }
+
+    let pixels: Vec<Color3> = samples
+        .get(..expected)
+        .unwrap_or_default()
+        .chunks(row_len.max(1))
+        .rev()
+        .flat_map(|row| row.chunks_exact(channels))
+        .map(|px| match *px {
+            [r, g, b] => color(r, g, b),
+            [v] => Color3::splat(v),
+            _ => Color3::BLACK,
+        })
+        .collect();
+
+    Image::from_pixels(width, height, pixels).ok_or_else(|| invalid("pixel count mismatch"))
+}
+
+#[expect(clippy::little_endian_bytes, reason = "a negative scale marks little-endian data")]
+const fn from_le(bytes: [u8; 4]) -> f32 { f32::from_le_bytes(bytes) }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenario_round_trip_keeps_radiance_above_one() {
+        let mut image = Image::new(3, 2);
+        image.set(0, 0, color(12.5, 0.25, 1e-3));
+        image.set(2, 1, color(0.0, 1000.0, 3.0));
+
+        let mut out = Vec::new();
+        PfmWriter.write(&image, &mut out).unwrap();
+        let decoded = read_pfm(&mut out.as_slice()).unwrap();
+
+        assert_eq!(decoded, image);
+    }
+
+    #[test]
+    fn scenario_big_endian_greyscale() {
+        let mut data = b"Pf\n2 1\n2.0\n".to_vec();
+        data.extend(0.5_f32.to_be_bytes());
+        data.extend(4.0_f32.to_be_bytes());
+
+        let image = read_pfm(&mut data.as_slice()).unwrap();
+        assert_eq!(image.get(0, 0), Some(Color3::splat(1.0)));
+        assert_eq!(image.get(1, 0), Some(Color3::splat(8.0)));
+    }
+}
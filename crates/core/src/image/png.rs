@@ -0,0 +1,525 @@
+//! PNG encoding and decoding.
+
+use std::io;
+
+use crate::color::TransferFunction;
+use crate::image::{Image, ImageWriter, invalid, zlib};
+use crate::prelude::{Color3, color};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// IDAT payloads are split into chunks of at most this many bytes.
+const IDAT_CHUNK: usize = 1 << 20;
+
+/// Bits per colour sample in a PNG file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PngBitDepth {
+    /// The same encoded bytes as PPM output.
+    #[default]
+    Eight,
+    /// Encoded 16-bit samples, for smoother gradients.
+    Sixteen,
+}
+
+/// Writes truecolour (RGB) PNG files.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PngWriter {
+    pub bit_depth: PngBitDepth,
+    pub transfer: TransferFunction,
+}
+
+impl PngWriter {
+    #[inline]
+    #[must_use]
+    pub const fn new(bit_depth: PngBitDepth) -> Self {
+        Self { bit_depth, transfer: TransferFunction::Gamma2 }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn with_transfer(self, transfer: TransferFunction) -> Self {
+        Self { transfer, ..self }
+    }
+}
+
+impl ImageWriter for PngWriter {
+    fn write(&self, image: &Image, out: &mut dyn io::Write) -> io::Result<()> {
+        let (depth, bytes_per_pixel) = match self.bit_depth {
+            PngBitDepth::Eight => (8, 3),
+            PngBitDepth::Sixteen => (16, 6),
+        };
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&image.width().to_be_bytes());
+        header.extend_from_slice(&image.height().to_be_bytes());
+        // Bit depth, colour type 2 (RGB), deflate, adaptive filtering, no
+        // interlacing.
+        header.extend_from_slice(&[depth, 2, 0, 0, 0]);
+
+        let mut raw = Vec::new();
+        let mut previous = Vec::new();
+        for row in image.rows() {
+            let current: Vec<u8> = match self.bit_depth {
+                PngBitDepth::Eight => row.iter().flat_map(|&c| self.transfer.to_u8(c)).collect(),
+                PngBitDepth::Sixteen => row
+                    .iter()
+                    .flat_map(|&c| self.transfer.to_u16(c))
+                    .flat_map(u16::to_be_bytes)
+                    .collect(),
+            };
+            if previous.is_empty() {
+                previous = vec![0; current.len()];
+            }
+            filter_row(&current, &previous, bytes_per_pixel, &mut raw);
+            previous = current;
+        }
+
+        out.write_all(&SIGNATURE)?;
+        write_chunk(out, *b"IHDR", &header)?;
+        for data in zlib::compress(&raw).chunks(IDAT_CHUNK) {
+            write_chunk(out, *b"IDAT", data)?;
+        }
+        write_chunk(out, *b"IEND", &[])?;
+        out.flush()
+    }
+}
+
+fn write_chunk(out: &mut dyn io::Write, kind: [u8; 4], data: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(data.len())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    out.write_all(&len.to_be_bytes())?;
+    out.write_all(&kind)?;
+    out.write_all(data)?;
+    out.write_all(&crc32(kind.iter().chain(data)).to_be_bytes())
+}
+
+// ---------------------------------------------------------------------------
+// Scanline filtering
+// ---------------------------------------------------------------------------
+
+/// PNG filter types, in the order they are numbered in the spec.
+const FILTERS: [u8; 5] = [0, 1, 2, 3, 4];
+
+/// Appends the filter byte and filtered bytes of `row` to `out`, picking the
+/// filter whose output has the smallest sum of absolute (signed) values — the
+/// heuristic recommended by the PNG specification.
+fn filter_row(row: &[u8], previous: &[u8], bpp: usize, out: &mut Vec<u8>) {
+    let best = FILTERS
+        .iter()
+        .map(|&kind| {
+            let filtered: Vec<u8> = (0..row.len())
+                .map(|i| {
+                    let x = row.get(i).copied().unwrap_or(0);
+                    x.wrapping_sub(predict(kind, row, previous, i, bpp))
+                })
+                .collect();
+            let cost: u64 = filtered.iter().map(|&b| u64::from(b.min(b.wrapping_neg()))).sum();
+            (cost, kind, filtered)
+        })
+        .min_by_key(|&(cost, ..)| cost);
+
+    if let Some((_, kind, filtered)) = best {
+        out.push(kind);
+        out.extend_from_slice(&filtered);
+    }
+}
+
+/// The value a filter predicts for byte `i` of `row` from already-coded
+/// neighbours: `a` to the left, `b` above, `c` above-left.
+pub(crate) fn predict(kind: u8, row: &[u8], previous: &[u8], i: usize, bpp: usize) -> u8 {
+    let left = i.checked_sub(bpp);
+    let a = left.and_then(|j| row.get(j)).copied().unwrap_or(0);
+    let b = previous.get(i).copied().unwrap_or(0);
+    let c = left.and_then(|j| previous.get(j)).copied().unwrap_or(0);
+
+    match kind {
+        1 => a,
+        2 => b,
+        3 => u8::try_from((u16::from(a) + u16::from(b)) >> 1).unwrap_or(0),
+        4 => paeth(a, b, c),
+        _ => 0,
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let (pa, pb, pc) =
+        ((p - i16::from(a)).abs(), (p - i16::from(b)).abs(), (p - i16::from(c)).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Decoding
+// ---------------------------------------------------------------------------
+
+/// Reads a non-interlaced PNG of any colour type and bit depth, decoding
+/// samples with `transfer`. Alpha is ignored.
+pub fn read_png(input: &mut dyn io::Read, transfer: TransferFunction) -> io::Result<Image> {
+    let mut file = Vec::new();
+    input.read_to_end(&mut file)?;
+
+    let mut rest = file.strip_prefix(&SIGNATURE).ok_or_else(|| invalid("not a PNG file"))?;
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut compressed = Vec::new();
+    while let Some((kind, data, tail)) = next_chunk(rest)? {
+        rest = tail;
+        match &kind {
+            b"IHDR" => header = Some(Header::parse(data)?),
+            b"PLTE" => palette = data,
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            // Ancillary chunks (lowercase first letter) may be skipped.
+            _ if kind.first().is_some_and(u8::is_ascii_lowercase) => {}
+            _ => {
+                let kind = String::from_utf8_lossy(&kind);
+                return Err(invalid(&format!("unsupported critical chunk {kind}")));
+            }
+        }
+    }
+    let header = header.ok_or_else(|| invalid("missing IHDR chunk"))?;
+
+    let stride = header.stride();
+    // Inflate no more than the header allows, then check the data fills it
+    // before sizing anything else from the header: a corrupt or hostile
+    // IHDR can claim any size.
+    let expected = stride
+        .checked_add(1)
+        .and_then(|line| line.checked_mul(header.height()))
+        .ok_or_else(|| invalid("image too large"))?;
+    let raw = zlib::decompress(&compressed, expected)?;
+    if raw.len() != expected {
+        return Err(invalid("image data does not match the IHDR size"));
+    }
+    let bpp = header.bits_per_pixel().div_ceil(8);
+    let mut previous = vec![0; stride];
+    let mut pixels = Vec::with_capacity(header.pixel_count());
+    let mut lines = raw.chunks_exact(stride + 1);
+    for _ in 0..header.height {
+        let line = lines.next().ok_or_else(|| invalid("truncated image data"))?;
+        let (&kind, filtered) = line.split_first().ok_or_else(|| invalid("empty scanline"))?;
+        if kind > 4 {
+            return Err(invalid(&format!("invalid filter type {kind}")));
+        }
+        let mut row = Vec::with_capacity(stride);
+        for (i, &byte) in filtered.iter().enumerate() {
+            let predicted = predict(kind, &row, &previous, i, bpp);
+            row.push(byte.wrapping_add(predicted));
+        }
+        pixels.extend(header.decode_row(&row, palette, transfer)?);
+        previous = row;
+    }
+
+    Image::from_pixels(header.width, header.height, pixels)
+        .ok_or_else(|| invalid("pixel count mismatch"))
+}
+
+/// A chunk's type and data, and the rest of the file after it.
+type Chunk<'a> = ([u8; 4], &'a [u8], &'a [u8]);
+
+/// Splits the next chunk off `file`, checking its CRC. `None` at the end.
+fn next_chunk(file: &[u8]) -> io::Result<Option<Chunk<'_>>> {
+    if file.is_empty() {
+        return Ok(None);
+    }
+    let truncated = || invalid("truncated PNG chunk");
+    let (len, rest) = file.split_at_checked(4).ok_or_else(truncated)?;
+    let len = usize::try_from(u32::from_be_bytes(len.try_into().unwrap_or_default()))
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let (kind, rest) = rest.split_at_checked(4).ok_or_else(truncated)?;
+    let (data, rest) = rest.split_at_checked(len).ok_or_else(truncated)?;
+    let (crc, rest) = rest.split_at_checked(4).ok_or_else(truncated)?;
+    if u32::from_be_bytes(crc.try_into().unwrap_or_default()) != crc32(kind.iter().chain(data)) {
+        return Err(invalid("PNG chunk CRC mismatch"));
+    }
+    Ok(Some((kind.try_into().unwrap_or_default(), data, rest)))
+}
+
+/// The fields of an `IHDR` chunk that affect decoding.
+struct Header {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> io::Result<Self> {
+        let &[w0, w1, w2, w3, h0, h1, h2, h3, bit_depth, color_type, _, _, interlace] = data else {
+            return Err(invalid("IHDR must be 13 bytes"));
+        };
+        let header = Self {
+            width: u32::from_be_bytes([w0, w1, w2, w3]),
+            height: u32::from_be_bytes([h0, h1, h2, h3]),
+            bit_depth,
+            color_type,
+        };
+        let valid_depths: &[u8] = match color_type {
+            0 => &[1, 2, 4, 8, 16],
+            3 => &[1, 2, 4, 8],
+            2 | 4 | 6 => &[8, 16],
+            _ => return Err(invalid(&format!("invalid colour type {color_type}"))),
+        };
+        if !valid_depths.contains(&bit_depth) {
+            return Err(invalid(&format!(
+                "invalid bit depth {bit_depth} for colour type {color_type}"
+            )));
+        }
+        if interlace != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "interlaced PNGs are not supported",
+            ));
+        }
+        Ok(header)
+    }
+
+    const fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize { self.channels() * usize::from(self.bit_depth) }
+
+    fn stride(&self) -> usize { (super::to_usize(self.width) * self.bits_per_pixel()).div_ceil(8) }
+
+    fn height(&self) -> usize { super::to_usize(self.height) }
+
+    fn pixel_count(&self) -> usize { super::to_usize(self.width) * self.height() }
+
+    /// Converts one unfiltered scanline to linear colours.
+    fn decode_row(
+        &self,
+        row: &[u8],
+        palette: &[u8],
+        transfer: TransferFunction,
+    ) -> io::Result<Vec<Color3>> {
+        let depth = u32::from(self.bit_depth);
+        let max = f64::from((1_u32 << depth) - 1);
+        let mut samples = Samples { row, depth, bit: 0 };
+        let width = super::to_usize(self.width);
+        let mut pixels = Vec::with_capacity(width);
+        for _ in 0..width {
+            let mut next = || samples.next().ok_or_else(|| invalid("truncated scanline"));
+            let encoded = match self.color_type {
+                3 => {
+                    let index = super::to_usize(next()?) * 3;
+                    let entry = palette
+                        .get(index..index + 3)
+                        .ok_or_else(|| invalid("palette index out of range"))?;
+                    match *entry {
+                        [r, g, b] => Color3::from([r, g, b]),
+                        _ => Color3::BLACK,
+                    }
+                }
+                0 | 4 => Color3::splat(f64::from(next()?) / max),
+                _ => {
+                    let (r, g, b) = (next()?, next()?, next()?);
+                    color(f64::from(r) / max, f64::from(g) / max, f64::from(b) / max)
+                }
+            };
+            // Skip alpha.
+            if matches!(self.color_type, 4 | 6) {
+                next()?;
+            }
+            pixels.push(color(
+                transfer.decode(encoded.r),
+                transfer.decode(encoded.g),
+                transfer.decode(encoded.b),
+            ));
+        }
+        Ok(pixels)
+    }
+}
+
+/// Big-endian samples of `depth` bits packed into a scanline.
+struct Samples<'a> {
+    row: &'a [u8],
+    depth: u32,
+    bit: usize,
+}
+
+impl Iterator for Samples<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        let byte = self.bit.div_euclid(8);
+        let value = match self.depth {
+            16 => u32::from(u16::from_be_bytes([*self.row.get(byte)?, *self.row.get(byte + 1)?])),
+            8 => u32::from(*self.row.get(byte)?),
+            depth => {
+                let shift = 8 - depth - u32::try_from(self.bit.rem_euclid(8)).ok()?;
+                (u32::from(*self.row.get(byte)?) >> shift) & ((1 << depth) - 1)
+            }
+        };
+        self.bit += super::to_usize(self.depth);
+        Some(value)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CRC-32
+// ---------------------------------------------------------------------------
+
+/// CRC-32 (ISO 3309) lookup table, built at compile time.
+const CRC_TABLE: [u32; 256] = crc_table();
+
+#[expect(clippy::indexing_slicing, reason = "const-evaluated; out of bounds fails the build")]
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut n = 0_u32;
+    let mut i = 0;
+    while i < table.len() {
+        let mut c = n;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 { 0xedb8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[i] = c;
+        n += 1;
+        i += 1;
+    }
+    table
+}
+
+pub(crate) fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let crc = bytes.into_iter().fold(u32::MAX, |crc, &byte| {
+        let index = (crc ^ u32::from(byte)) & 0xff;
+        let entry = usize::try_from(index).ok().and_then(|i| CRC_TABLE.get(i)).copied();
+        entry.unwrap_or(0) ^ (crc >> 8)
+    });
+    !crc
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Splits a PNG file into `(type, data)` chunks, checking every CRC.
+    fn chunks(file: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        let (signature, mut rest) = file.split_at(8);
+        assert_eq!(signature, SIGNATURE);
+        let mut out = Vec::new();
+        while !rest.is_empty() {
+            let (len, tail) = rest.split_at(4);
+            let len = usize::try_from(u32::from_be_bytes(len.try_into().unwrap())).unwrap();
+            let (kind, tail) = tail.split_at(4);
+            let (data, tail) = tail.split_at(len);
+            let (crc, tail) = tail.split_at(4);
+            assert_eq!(u32::from_be_bytes(crc.try_into().unwrap()), crc32(kind.iter().chain(data)));
+            out.push((kind.try_into().unwrap(), data.to_vec()));
+            rest = tail;
+        }
+        out
+    }
+
+    #[test]
+    fn scenario_crc_of_iend() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn scenario_chunk_layout() {
+        let mut image = Image::new(3, 2);
+        image.set(1, 1, color(0.25, 0.5, 1.0));
+
+        for (depth, expected) in [(PngBitDepth::Eight, 8), (PngBitDepth::Sixteen, 16)] {
+            let mut file = Vec::new();
+            PngWriter::new(depth).write(&image, &mut file).unwrap();
+
+            let chunks = chunks(&file);
+            let kinds: Vec<_> = chunks.iter().map(|&(kind, _)| kind).collect();
+            assert_eq!(kinds, [*b"IHDR", *b"IDAT", *b"IEND"]);
+            let header = chunks.first().map(|chunk| chunk.1.as_slice());
+            assert_eq!(header, Some(&[0, 0, 0, 3, 0, 0, 0, 2, expected, 2, 0, 0, 0][..]));
+        }
+    }
+
+    fn testdata(name: &str) -> Vec<u8> {
+        std::fs::read(format!("{}/testdata/{name}", env!("CARGO_MANIFEST_DIR"))).unwrap()
+    }
+
+    #[test]
+    fn scenario_round_trip_both_depths() {
+        let mut image = Image::new(7, 5);
+        for (i, pixel) in (0..).zip(image.pixels_mut()) {
+            *pixel = color(f64::from(i) / 35.0, 0.5, 1.0 - f64::from(i) / 35.0);
+        }
+        for depth in [PngBitDepth::Eight, PngBitDepth::Sixteen] {
+            let writer = PngWriter::new(depth).with_transfer(TransferFunction::Srgb);
+            let mut first = Vec::new();
+            writer.write(&image, &mut first).unwrap();
+
+            let decoded = read_png(&mut first.as_slice(), TransferFunction::Srgb).unwrap();
+            let mut second = Vec::new();
+            writer.write(&decoded, &mut second).unwrap();
+            assert_eq!(first, second);
+        }
+    }
+
+    #[test]
+    fn scenario_palette_with_sub_byte_indices() {
+        let file = testdata("palette.png");
+        let image = read_png(&mut file.as_slice(), TransferFunction::Linear).unwrap();
+        let palette = [Color3::RED, Color3::GREEN, Color3::BLUE, Color3::WHITE];
+        assert_eq!((image.width(), image.height()), (5, 3));
+        for y in 0_u32..3 {
+            for x in 0..5 {
+                let index = usize::try_from((x + y).rem_euclid(4)).unwrap();
+                assert_eq!(image.get(x, y), palette.get(index).copied());
+            }
+        }
+    }
+
+    #[test]
+    fn scenario_sixteen_bit_grey_ignores_alpha() {
+        let file = testdata("grey_alpha.png");
+        let image = read_png(&mut file.as_slice(), TransferFunction::Linear).unwrap();
+        assert_eq!(image.pixels(), [Color3::BLACK, Color3::WHITE]);
+    }
+
+    #[test]
+    fn scenario_corrupt_files_are_errors() {
+        let mut file = testdata("palette.png");
+        assert!(read_png(&mut file.get(..20).unwrap(), TransferFunction::Srgb).is_err());
+        *file.last_mut().unwrap() ^= 1; // IEND CRC
+        assert!(read_png(&mut file.as_slice(), TransferFunction::Srgb).is_err());
+    }
+
+    #[test]
+    fn scenario_oversized_header_is_an_error() {
+        // 100000 × 100000 RGB with no image data: must fail, not allocate.
+        let mut file = SIGNATURE.to_vec();
+        let [a, b, c, d] = 100_000_u32.to_be_bytes();
+        write_chunk(&mut file, *b"IHDR", &[a, b, c, d, a, b, c, d, 8, 2, 0, 0, 0]).unwrap();
+        write_chunk(&mut file, *b"IDAT", &zlib::compress(&[])).unwrap();
+        write_chunk(&mut file, *b"IEND", &[]).unwrap();
+        let err = read_png(&mut file.as_slice(), TransferFunction::Srgb).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn scenario_image_data_is_inflated_no_further_than_the_header() {
+        // 1 × 1 RGB whose few kilobytes of IDAT would inflate to 1 MiB.
+        let mut file = SIGNATURE.to_vec();
+        write_chunk(&mut file, *b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 0]).unwrap();
+        write_chunk(&mut file, *b"IDAT", &zlib::compress(&vec![0; 1 << 20])).unwrap();
+        write_chunk(&mut file, *b"IEND", &[]).unwrap();
+        let err = read_png(&mut file.as_slice(), TransferFunction::Srgb).unwrap_err();
+        assert_eq!(err.to_string(), "zlib data larger than expected");
+    }
+}
@@ -0,0 +1,226 @@
+//! Netpbm PPM encoding and decoding.
+
+use std::io;
+
+use crate::color::TransferFunction;
+use crate::image::{Image, ImageWriter, Tokens, invalid};
+use crate::prelude::{Color3, color};
+
+/// Sample encoding of a PPM file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PpmFormat {
+    /// `P3`: one decimal `r g b` triple per line. This is what the books print
+    /// to stdout.
+    #[default]
+    Ascii,
+    /// `P6`: raw bytes, about a quarter of the size of `P3`.
+    Binary,
+}
+
+/// Writes PPM with 8-bit samples, encoded with `transfer` (gamma 2 by
+/// default).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PpmWriter {
+    pub format: PpmFormat,
+    pub transfer: TransferFunction,
+}
+
+impl PpmWriter {
+    #[inline]
+    #[must_use]
+    pub const fn new(format: PpmFormat) -> Self {
+        Self { format, transfer: TransferFunction::Gamma2 }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn with_transfer(self, transfer: TransferFunction) -> Self {
+        Self { transfer, ..self }
+    }
+}
+
+impl ImageWriter for PpmWriter {
+    fn write(&self, image: &Image, out: &mut dyn io::Write) -> io::Result<()> {
+        match self.format {
+            PpmFormat::Ascii => {
+                writeln!(out, "P3\n{} {}\n255", image.width(), image.height())?;
+                for &pixel in image.pixels() {
+                    let [r, g, b] = self.transfer.to_u8(pixel);
+                    writeln!(out, "{r} {g} {b}")?;
+                }
+            }
+            PpmFormat::Binary => {
+                writeln!(out, "P6\n{} {}\n255", image.width(), image.height())?;
+                let bytes: Vec<u8> =
+                    image.pixels().iter().flat_map(|&pixel| self.transfer.to_u8(pixel)).collect();
+                out.write_all(&bytes)?;
+            }
+        }
+        out.flush()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Decoding
+// ---------------------------------------------------------------------------
+
+/// Reads a `P3` or `P6` PPM into linear colours, decoding samples with
+/// `transfer`.
+///
+/// With the writer's transfer function (gamma 2 by default) an image
+/// survives a write/read round trip unchanged at 8 bits.
+pub fn read_ppm(input: &mut dyn io::Read, transfer: TransferFunction) -> io::Result<Image> {
+    let mut data = Vec::new();
+    input.read_to_end(&mut data)?;
+
+    let mut header = Tokens::new(&data);
+    let magic = header.next_token().ok_or_else(|| invalid("missing PPM magic number"))?;
+    let format = match magic {
+        b"P3" => PpmFormat::Ascii,
+        b"P6" => PpmFormat::Binary,
+        _ => return Err(invalid("not a P3 or P6 PPM file")),
+    };
+    let width: u32 = header.parse("width")?;
+    let height: u32 = header.parse("height")?;
+    let maxval: u32 = header.parse("maximum value")?;
+    if maxval == 0 || maxval > u32::from(u16::MAX) {
+        return Err(invalid(&format!("maximum value {maxval} is outside 1–65535")));
+    }
+
+    let count = usize::try_from(u64::from(width) * u64::from(height) * 3)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let samples: Vec<u32> = match format {
+        PpmFormat::Ascii => core::iter::repeat_with(|| header.parse("sample"))
+            .take(count)
+            .collect::<io::Result<_>>()?,
+        PpmFormat::Binary => {
+            // Exactly one whitespace byte separates the header from the raster.
+            let raster = data.get(header.pos + 1..).unwrap_or_default();
+            if maxval < 256 {
+                raster.iter().take(count).map(|&b| u32::from(b)).collect()
+            } else {
+                raster
+                    .chunks_exact(2)
+                    .take(count)
+                    .map(|pair| pair.iter().fold(0, |acc, &b| (acc << 8) | u32::from(b)))
+                    .collect()
+            }
+        }
+    };
+    if samples.len() < count {
+        return Err(invalid(&format!("expected {count} samples, found {}", samples.len())));
+    }
+    if let Some(sample) = samples.iter().find(|&&sample| sample > maxval) {
+        return Err(invalid(&format!("sample {sample} exceeds the maximum value {maxval}")));
+    }
+
+    let pixels = samples
+        .chunks_exact(3)
+        .map(|rgb| {
+            let encoded = match *rgb {
+                [r, g, b] if maxval == 255 => {
+                    Color3::from([r, g, b].map(|v| u8::try_from(v).unwrap_or(u8::MAX)))
+                }
+                [r, g, b] => {
+                    let scale = 1.0 / f64::from(maxval);
+                    color(f64::from(r) * scale, f64::from(g) * scale, f64::from(b) * scale)
+                }
+                _ => Color3::BLACK,
+            };
+            color(
+                transfer.decode(encoded.r),
+                transfer.decode(encoded.g),
+                transfer.decode(encoded.b),
+            )
+        })
+        .collect();
+
+    Image::from_pixels(width, height, pixels).ok_or_else(|| invalid("pixel count mismatch"))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient() -> Image {
+        let mut image = Image::new(16, 4);
+        for y in 0..4 {
+            for x in 0..16 {
+                image.set(x, y, color(f64::from(x) / 15.0, f64::from(y) / 3.0, 0.25));
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn scenario_ascii_layout() {
+        let mut image = Image::new(2, 1);
+        image.set(1, 0, Color3::WHITE);
+
+        let mut out = Vec::new();
+        PpmWriter::default().write(&image, &mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "P3\n2 1\n255\n0 0 0\n255 255 255\n");
+    }
+
+    #[test]
+    fn scenario_round_trip_both_formats() {
+        let image = gradient();
+        for format in [PpmFormat::Ascii, PpmFormat::Binary] {
+            let mut first = Vec::new();
+            PpmWriter::new(format).write(&image, &mut first).unwrap();
+
+            let decoded = read_ppm(&mut first.as_slice(), TransferFunction::Gamma2).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (16, 4));
+
+            let mut second = Vec::new();
+            PpmWriter::new(format).write(&decoded, &mut second).unwrap();
+            assert_eq!(first, second);
+        }
+    }
+
+    #[test]
+    fn scenario_header_comments_and_wide_samples() {
+        let data = b"P6 # binary\n1 1\n# max\n65535\n\xff\xff\x00\x00\x80\x00";
+        let image = read_ppm(&mut data.as_slice(), TransferFunction::Gamma2).unwrap();
+        let [r, g, b] = <[u8; 3]>::from(image.get(0, 0).unwrap());
+        assert_eq!((r, g), (255, 0));
+        assert!((127..=128).contains(&b));
+    }
+
+    #[test]
+    fn scenario_book_asset_reencodes_identically() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../book1/assets/01.ppm");
+        let original = std::fs::read(path).unwrap();
+
+        let image = read_ppm(&mut original.as_slice(), TransferFunction::Gamma2).unwrap();
+        assert_eq!((image.width(), image.height()), (256, 256));
+
+        let mut out = Vec::new();
+        PpmWriter::default().write(&image, &mut out).unwrap();
+        assert_eq!(out, original);
+    }
+
+    #[test]
+    fn scenario_truncated_raster_is_an_error() {
+        let data = b"P6\n2 2\n255\n\x00\x00\x00";
+        assert!(read_ppm(&mut data.as_slice(), TransferFunction::Gamma2).is_err());
+    }
+
+    #[test]
+    fn scenario_samples_above_maxval_are_an_error() {
+        let cases: [(&[u8], _); 2] = [
+            (b"P3\n1 1\n255\n0 300 0\n", "sample 300 exceeds the maximum value 255"),
+            (b"P6\n1 1\n100\n\x00\x65\x00", "sample 101 exceeds the maximum value 100"),
+        ];
+        for (mut data, message) in cases {
+            let err = read_ppm(&mut data, TransferFunction::Gamma2).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert_eq!(err.to_string(), message);
+        }
+    }
+}
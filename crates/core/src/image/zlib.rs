@@ -0,0 +1,522 @@
+//! Minimal zlib (RFC 1950) streams over DEFLATE (RFC 1951).
+//!
+//! The encoder does greedy LZ77 matching with hash chains, emitted as a
+//! single block of fixed Huffman codes. That gets most of the way to
+//! `zlib -6` on rendered images without a dependency or dynamic-table
+//! construction. The decoder handles every block type, so it reads files
+//! from other tools too.
+
+use std::io;
+
+use crate::image::invalid;
+
+/// Sliding-window size; also the largest back-reference distance.
+const WINDOW: usize = 0x8000;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// How many earlier positions with the same hash to try before giving up.
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: u32 = 15;
+
+pub(crate) const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+pub(crate) const LENGTH_EXTRA: [u8; 29] =
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+pub(crate) const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 0x4001, 24577,
+];
+pub(crate) const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Compresses `data` into a complete zlib stream.
+pub(crate) fn compress(data: &[u8]) -> Vec<u8> {
+    // CMF = deflate with a 32 KiB window; FLG makes the pair divisible by 31.
+    let mut bits = BitWriter { out: vec![0x78, 0x9c], acc: 0, len: 0 };
+
+    // BFINAL = 1, BTYPE = 01 (fixed Huffman codes).
+    bits.write(0b011, 3);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW];
+    let mut pos = 0;
+    while pos < data.len() {
+        let (length, distance) = longest_match(data, pos, &head, &prev);
+
+        let advance = if length >= MIN_MATCH {
+            bits.write_length(length);
+            bits.write_distance(distance);
+            length
+        } else {
+            bits.write_symbol(data.get(pos).copied().map_or(0, u16::from));
+            1
+        };
+
+        for p in pos..pos + advance {
+            if let Some(h) = hash_at(data, p) {
+                if let Some(slot) = prev.get_mut(p & (WINDOW - 1)) {
+                    *slot = head.get(h).copied().unwrap_or(usize::MAX);
+                }
+                if let Some(slot) = head.get_mut(h) {
+                    *slot = p;
+                }
+            }
+        }
+        pos += advance;
+    }
+
+    bits.write_symbol(256); // end of block
+    bits.flush();
+
+    let mut out = bits.out;
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+/// Returns the longest earlier match for the bytes at `pos` as
+/// `(length, distance)`; the length is 0 if there is none.
+fn longest_match(data: &[u8], pos: usize, head: &[usize], prev: &[usize]) -> (usize, usize) {
+    let Some(h) = hash_at(data, pos) else { return (0, 0) };
+    let rest = data.get(pos..).unwrap_or_default();
+    let limit = rest.len().min(MAX_MATCH);
+
+    let mut best = (0, 0);
+    let mut candidate = head.get(h).copied().unwrap_or(usize::MAX);
+    for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || pos - candidate > WINDOW {
+            break;
+        }
+        let earlier = data.get(candidate..).unwrap_or_default();
+        let length = earlier.iter().zip(rest).take(limit).take_while(|&(a, b)| a == b).count();
+        if length > best.0 {
+            best = (length, pos - candidate);
+            if length == limit {
+                break;
+            }
+        }
+        let next = prev.get(candidate & (WINDOW - 1)).copied().unwrap_or(usize::MAX);
+        // Slots are reused as the window slides; stale links point forwards.
+        if next >= candidate {
+            break;
+        }
+        candidate = next;
+    }
+    best
+}
+
+fn hash_at(data: &[u8], pos: usize) -> Option<usize> {
+    match *data.get(pos..pos + MIN_MATCH)? {
+        [a, b, c] => {
+            let h = (u32::from(a) << 10) ^ (u32::from(b) << 5) ^ u32::from(c);
+            usize::try_from(h & ((1 << HASH_BITS) - 1)).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Adler-32 checksum of `data`, as stored at the end of a zlib stream.
+pub(crate) fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 0xfff1; // 65521, the largest prime below 2^16
+    // Largest run that cannot overflow `b` before reducing.
+    const NMAX: usize = 5_552;
+
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for chunk in data.chunks(NMAX) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a = a.rem_euclid(MOD);
+        b = b.rem_euclid(MOD);
+    }
+    (b << 16) | a
+}
+
+/// Packs variable-width codes least-significant bit first.
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u64,
+    len: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, count: u32) {
+        self.acc |= u64::from(value) << self.len;
+        self.len += count;
+        while self.len >= 8 {
+            self.out.push(low_byte(self.acc));
+            self.acc >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    /// Huffman codes are defined most-significant bit first, so they are
+    /// reversed before packing.
+    fn write_code(&mut self, code: u32, count: u32) {
+        self.write(code.reverse_bits() >> (32 - count), count);
+    }
+
+    /// Writes a literal/length symbol with the fixed code table.
+    fn write_symbol(&mut self, symbol: u16) {
+        let s = u32::from(symbol);
+        match symbol {
+            0..=143 => self.write_code(0x30 + s, 8),
+            144..=255 => self.write_code(0x190 + s - 144, 9),
+            256..=279 => self.write_code(s - 256, 7),
+            _ => self.write_code(0xc0 + s - 280, 8),
+        }
+    }
+
+    fn write_length(&mut self, length: usize) {
+        let length = u16::try_from(length).unwrap_or(u16::MAX);
+        let code = LENGTH_BASE.iter().rposition(|&base| base <= length).unwrap_or(0);
+        let (base, extra) = (LENGTH_BASE.get(code), LENGTH_EXTRA.get(code));
+        self.write_symbol(257 + u16::try_from(code).unwrap_or(0));
+        self.write(
+            u32::from(length - base.copied().unwrap_or(3)),
+            extra.copied().map_or(0, u32::from),
+        );
+    }
+
+    fn write_distance(&mut self, distance: usize) {
+        let distance = u16::try_from(distance).unwrap_or(u16::MAX);
+        let code = DIST_BASE.iter().rposition(|&base| base <= distance).unwrap_or(0);
+        let (base, extra) = (DIST_BASE.get(code), DIST_EXTRA.get(code));
+        self.write_code(u32::try_from(code).unwrap_or(0), 5);
+        self.write(
+            u32::from(distance - base.copied().unwrap_or(1)),
+            extra.copied().map_or(0, u32::from),
+        );
+    }
+
+    /// Pads the final partial byte with zero bits.
+    fn flush(&mut self) {
+        if self.len > 0 {
+            self.out.push(low_byte(self.acc));
+        }
+        self.acc = 0;
+        self.len = 0;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Decoding
+// ---------------------------------------------------------------------------
+
+/// Order in which code-length code lengths are stored in a dynamic block
+/// header.
+const CODE_LENGTH_ORDER: [usize; 19] =
+    [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+const MAX_BITS: usize = 15;
+
+/// Decompresses a complete zlib stream, checking its Adler-32 trailer.
+///
+/// Fails as soon as the output would exceed `limit` bytes, so that a small
+/// hostile stream can't expand without bound.
+pub(crate) fn decompress(data: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+    let [cmf, flg, ..] = *data else { return Err(invalid("truncated zlib header")) };
+    if cmf & 0x0f != 8 || ((u16::from(cmf) << 8) | u16::from(flg)).rem_euclid(31) != 0 {
+        return Err(invalid("not a deflate zlib stream"));
+    }
+    if flg & 0x20 != 0 {
+        return Err(invalid("zlib preset dictionaries are not supported"));
+    }
+
+    let mut bits = BitReader { data: data.get(2..).unwrap_or_default(), pos: 0, acc: 0, len: 0 };
+    let mut out = Vec::new();
+    loop {
+        let last = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => inflate_stored(&mut bits, &mut out, limit)?,
+            1 => {
+                let (literals, distances) = fixed_tables();
+                inflate_block(&mut bits, &literals, &distances, &mut out, limit)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_tables(&mut bits)?;
+                inflate_block(&mut bits, &literals, &distances, &mut out, limit)?;
+            }
+            _ => return Err(invalid("invalid deflate block type")),
+        }
+        if last {
+            break;
+        }
+    }
+
+    bits.align();
+    let trailer = bits.take(4)?;
+    let expected = trailer.iter().fold(0, |acc, &b| (acc << 8) | u32::from(b));
+    if expected != adler32(&out) {
+        return Err(invalid("zlib checksum mismatch"));
+    }
+    Ok(out)
+}
+
+fn inflate_stored(bits: &mut BitReader<'_>, out: &mut Vec<u8>, limit: usize) -> io::Result<()> {
+    bits.align();
+    let header = bits.take(4)?;
+    let (len, nlen) = match *header {
+        [a, b, c, d] => (u16::from_be_bytes([b, a]), u16::from_be_bytes([d, c])),
+        _ => return Err(invalid("truncated stored block")),
+    };
+    if len != !nlen {
+        return Err(invalid("corrupt stored block length"));
+    }
+    check_room(out, usize::from(len), limit)?;
+    out.extend_from_slice(bits.take(usize::from(len))?);
+    Ok(())
+}
+
+fn inflate_block(
+    bits: &mut BitReader<'_>,
+    literals: &Huffman,
+    distances: &Huffman,
+    out: &mut Vec<u8>,
+    limit: usize,
+) -> io::Result<()> {
+    loop {
+        let symbol = literals.decode(bits)?;
+        match symbol {
+            0..=255 => {
+                check_room(out, 1, limit)?;
+                out.push(u8::try_from(symbol).unwrap_or(0));
+            }
+            256 => return Ok(()),
+            _ => {
+                let code = usize::from(symbol - 257);
+                let length = extra(bits, LENGTH_BASE.get(code), LENGTH_EXTRA.get(code))?;
+                let code = usize::from(distances.decode(bits)?);
+                let distance = extra(bits, DIST_BASE.get(code), DIST_EXTRA.get(code))?;
+
+                let start = out
+                    .len()
+                    .checked_sub(distance)
+                    .ok_or_else(|| invalid("deflate distance too far back"))?;
+                check_room(out, length, limit)?;
+                // Byte by byte: the source may overlap what is being written.
+                for i in start..start + length {
+                    let byte = out.get(i).copied().unwrap_or(0);
+                    out.push(byte);
+                }
+            }
+        }
+    }
+}
+
+/// Fails if `extra` more bytes would take `out` past `limit`.
+fn check_room(out: &[u8], extra: usize, limit: usize) -> io::Result<()> {
+    if out.len().saturating_add(extra) > limit {
+        return Err(invalid("zlib data larger than expected"));
+    }
+    Ok(())
+}
+
+/// `base` plus its extra bits, for length and distance codes.
+fn extra(bits: &mut BitReader<'_>, base: Option<&u16>, extra: Option<&u8>) -> io::Result<usize> {
+    let (Some(&base), Some(&extra)) = (base, extra) else {
+        return Err(invalid("invalid deflate length or distance code"));
+    };
+    Ok(usize::from(base) + to_usize(bits.read(u32::from(extra))?))
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0; 288];
+    for (symbol, length) in lengths.iter_mut().enumerate() {
+        *length = match symbol {
+            0..=143 | 280.. => 8,
+            144..=255 => 9,
+            _ => 7,
+        };
+    }
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_tables(bits: &mut BitReader<'_>) -> io::Result<(Huffman, Huffman)> {
+    let literal_count = to_usize(bits.read(5)?) + 257;
+    let distance_count = to_usize(bits.read(5)?) + 1;
+    let code_length_count = to_usize(bits.read(4)?) + 4;
+
+    let mut code_lengths = [0; 19];
+    for &index in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        if let Some(length) = code_lengths.get_mut(index) {
+            *length = u8::try_from(bits.read(3)?).unwrap_or(0);
+        }
+    }
+    let code_lengths = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let symbol = code_lengths.decode(bits)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (u8::try_from(symbol).unwrap_or(0), 1),
+            16 => {
+                let previous = lengths.last().copied();
+                (
+                    previous.ok_or_else(|| invalid("repeat with no previous length"))?,
+                    3 + bits.read(2)?,
+                )
+            }
+            17 => (0, 3 + bits.read(3)?),
+            _ => (0, 11 + bits.read(7)?),
+        };
+        lengths.extend(core::iter::repeat_n(value, to_usize(repeat)));
+    }
+    if lengths.len() > literal_count + distance_count {
+        return Err(invalid("code lengths overrun the table"));
+    }
+
+    let (literals, distances) = lengths.split_at(literal_count);
+    Ok((Huffman::new(literals), Huffman::new(distances)))
+}
+
+/// A canonical Huffman code, decoded one bit at a time.
+struct Huffman {
+    /// Number of codes of each bit length.
+    counts: [u16; MAX_BITS + 1],
+    /// Symbols ordered by code.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0; MAX_BITS + 1];
+        for &length in lengths {
+            if let Some(count) = counts.get_mut(usize::from(length)) {
+                *count += 1;
+            }
+        }
+        counts[0] = 0;
+
+        let mut symbols = Vec::with_capacity(lengths.len());
+        for length in 1..=MAX_BITS {
+            let with_length = lengths.iter().zip(0..).filter(|&(&l, _)| usize::from(l) == length);
+            symbols.extend(with_length.map(|(_, symbol)| symbol));
+        }
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut BitReader<'_>) -> io::Result<u16> {
+        // `code` is the code read so far; `first` the first code of the
+        // current length; `index` the position of that code in `symbols`.
+        let (mut code, mut first, mut index) = (0_usize, 0_usize, 0_usize);
+        for &count in self.counts.iter().skip(1) {
+            code |= to_usize(bits.read(1)?);
+            let count = usize::from(count);
+            if code < first + count {
+                return self
+                    .symbols
+                    .get(index + code - first)
+                    .copied()
+                    .ok_or_else(|| invalid("invalid Huffman code"));
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid("invalid Huffman code"))
+    }
+}
+
+/// Reads variable-width values least-significant bit first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    acc: u64,
+    len: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn read(&mut self, count: u32) -> io::Result<u32> {
+        while self.len < count {
+            let byte = self.data.get(self.pos).ok_or_else(|| invalid("truncated deflate data"))?;
+            self.acc |= u64::from(*byte) << self.len;
+            self.pos += 1;
+            self.len += 8;
+        }
+        let value = self.acc & ((1 << count) - 1);
+        self.acc >>= count;
+        self.len -= count;
+        Ok(u32::try_from(value).unwrap_or(0))
+    }
+
+    /// Drops the bits left in the current byte.
+    const fn align(&mut self) {
+        // Whole bytes still buffered are handed back to `take`.
+        self.pos -= to_usize(self.len >> 3);
+        self.acc = 0;
+        self.len = 0;
+    }
+
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let bytes =
+            self.data.get(self.pos..self.pos + n).ok_or_else(|| invalid("truncated data"))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+}
+
+#[expect(clippy::as_conversions)]
+const fn to_usize(n: u32) -> usize { n as usize }
+
+#[expect(clippy::as_conversions, clippy::cast_possible_truncation)]
+const fn low_byte(v: u64) -> u8 { v as u8 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenario_round_trip() {
+        let text = b"the quick brown fox jumps over the lazy dog. ".repeat(50);
+        let noise: Vec<u8> =
+            (0_u32..5000).map(|i| low_byte(u64::from((i * 7919) ^ (i >> 3)))).collect();
+        for data in [&[][..], b"a", &text, &noise] {
+            assert_eq!(decompress(&compress(data), data.len()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn scenario_stored_and_dynamic_blocks_from_zlib() {
+        // zlib.compress(b"hello hello", 0): a single stored block.
+        let stored = [
+            0x78, 0x01, 0x01, 0x0b, 0x00, 0xf4, 0xff, b'h', b'e', b'l', b'l', b'o', b' ', b'h',
+            b'e', b'l', b'l', b'o', 0x19, 0x91, 0x04, 0x49,
+        ];
+        assert_eq!(decompress(&stored, 11).unwrap(), b"hello hello");
+        assert!(decompress(&stored, 10).is_err());
+
+        // zlib.compress(b"".join(b"%d squared is %d; " % (i, i * i) for i in
+        // range(200)), 9) uses a dynamic Huffman block.
+        let dynamic = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/dynamic.zlib"));
+        let expected: Vec<u8> =
+            (0..200).flat_map(|i| format!("{i} squared is {}; ", i * i).into_bytes()).collect();
+        assert_eq!(decompress(dynamic, usize::MAX).unwrap(), expected);
+    }
+
+    #[test]
+    fn scenario_corruption_is_an_error() {
+        let mut data = compress(b"some bytes worth checking");
+        *data.last_mut().unwrap() ^= 1;
+        assert!(decompress(&data, usize::MAX).is_err());
+        assert!(decompress(&[0x78], usize::MAX).is_err());
+    }
+
+    #[test]
+    fn scenario_output_limit() {
+        // A few kilobytes that would inflate to a mebibyte.
+        let zeros = vec![0; 1 << 20];
+        let bomb = compress(&zeros);
+        assert!(bomb.len() * 100 < zeros.len());
+        let error = decompress(&bomb, 1000).unwrap_err();
+        assert_eq!(error.to_string(), "zlib data larger than expected");
+        assert_eq!(decompress(&bomb, zeros.len()).unwrap(), zeros);
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use rand::prelude::Rng;
+
+use crate::prelude::{AABB, HitRecord, Hittable, Interval, Point3, Ray, Transform, Vec3};
+
+/// A shared object placed in the scene by a [`Transform`].
+///
+/// Any number of instances can point at the same object, so a mesh is stored
+/// once however many times it appears. Rays are moved into the object's own
+/// space for the intersection test and the hit is moved back out.
+pub struct Instance {
+    object: Arc<dyn Hittable>,
+    /// Object space to world space.
+    transform: Transform,
+    bbox: AABB,
+}
+
+impl Instance {
+    #[must_use]
+    pub fn new(object: Arc<dyn Hittable>, transform: Transform) -> Self {
+        let bbox = transform.bounds(object.bounding_box());
+        Self { object, transform, bbox }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn object(&self) -> &Arc<dyn Hittable> { &self.object }
+
+    #[inline]
+    #[must_use]
+    pub const fn transform(&self) -> &Transform { &self.transform }
+}
+
+impl Hittable for Instance {
+    fn bounding_box(&self) -> AABB { self.bbox }
+
+    fn hit(&self, ray: &Ray, t: Interval) -> Option<HitRecord> {
+        hit_transformed(self.object.as_ref(), &self.transform, ray, t)
+    }
+
+    fn is_sampleable(&self) -> bool { self.object.is_sampleable() }
+
+    /// A linear map `M` stretches solid angle unevenly: a direction `ω`
+    /// whose object-space counterpart is `M⁻¹ω` has its density scaled by
+    /// |det M⁻¹| / |M⁻¹ω̂|³.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> Option<f64> {
+        let inverse = self.transform.inverse();
+        let local = inverse.vector(direction.unit());
+        let density = self.object.pdf_value(inverse.point(origin), local)?;
+        Some(density * inverse.determinant().abs() / local.length().powi(3))
+    }
+
+    fn random(&self, rng: &mut dyn Rng, origin: Point3) -> Option<Vec3> {
+        let local_origin = self.transform.inverse().point(origin);
+        self.object.random(rng, local_origin).map(|local| self.transform.vector(local))
+    }
+}
+
+/// Intersects `object` as placed in world space by `transform`.
+pub(crate) fn hit_transformed(
+    object: &dyn Hittable,
+    transform: &Transform,
+    ray: &Ray,
+    t: Interval,
+) -> Option<HitRecord> {
+    // The object-space direction is not renormalised, so `t` means the same
+    // point along both rays and needs no conversion.
+    let local = transform.inverse().ray(ray);
+    let mut rec = object.hit(&local, t)?;
+
+    rec.p = transform.point(rec.p);
+    // The normal already faces the ray, and the inverse transpose keeps it
+    // that way: d · n is the same in both spaces.
+    rec.normal = transform.normal(rec.normal).unit();
+    Some(rec)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use shared::TOLERANCE;
+
+    use super::*;
+    use crate::prelude::{Color3, Lambertian, Material, Sphere, make_box, point3, vec3};
+
+    fn grey() -> Arc<dyn Material> { Arc::new(Lambertian::new(Color3::new(0.5, 0.5, 0.5))) }
+
+    fn unit_sphere() -> Arc<dyn Hittable> { Arc::new(Sphere::new(Point3::ZERO, None, 1.0, grey())) }
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < TOLERANCE, "{a} != {b}");
+    }
+
+    #[test]
+    fn scenario_shared_object_placed_twice() {
+        let sphere = unit_sphere();
+        let left = Instance::new(Arc::clone(&sphere), Transform::translate(vec3(-3, 0, 0)));
+        let right = Instance::new(sphere, Transform::translate(vec3(3, 0, 0)));
+
+        let ray = Ray::new(point3(3, 0, 5), -Vec3::Z, None);
+        assert!(left.hit(&ray, Interval::new(0.001, f64::INFINITY)).is_none());
+        let rec = right.hit(&ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+        shared::assert_fuzzy_eq!(rec.t, 4.0);
+        assert_close(rec.p, point3(3, 0, 1));
+        assert_close(rec.normal, Vec3::Z);
+        assert!(rec.is_front_face);
+    }
+
+    #[test]
+    fn scenario_scaled_normals() {
+        // An ellipsoid twice as wide in x; hit it on the slanted part.
+        let ellipsoid = Instance::new(unit_sphere(), Transform::scale(vec3(2, 1, 1)));
+        let ray = Ray::new(point3(1, 0, 5), -Vec3::Z, None);
+        let rec = ellipsoid.hit(&ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+
+        let z = 0.75_f64.sqrt();
+        assert_close(rec.p, point3(1.0, 0.0, z));
+        // The gradient of x²/4 + y² + z² at p.
+        assert_close(rec.normal, vec3(0.5, 0.0, 2.0 * z).unit());
+
+        // From inside, the normal still opposes the ray.
+        let inside = Ray::new(Point3::ZERO, Vec3::X, None);
+        let rec = ellipsoid.hit(&inside, Interval::new(0.001, f64::INFINITY)).unwrap();
+        shared::assert_fuzzy_eq!(rec.t, 2.0);
+        assert!(!rec.is_front_face);
+        assert_close(rec.normal, -Vec3::X);
+    }
+
+    #[test]
+    fn scenario_world_bounds() {
+        let cube: Arc<dyn Hittable> = Arc::new(make_box(Point3::ZERO, Point3::ONE, &grey()));
+        let instance = Instance::new(
+            cube,
+            Transform::translate(vec3(10, 0, 0)) * Transform::rotate(Vec3::Y, 45.0),
+        );
+        let bbox = instance.bounding_box();
+        let half = 0.5_f64.sqrt();
+        assert!(bbox.x.contains(10.0) && bbox.x.contains(10.0 + 2.0 * half - TOLERANCE));
+        assert!(bbox.z.contains(-half + TOLERANCE) && bbox.z.contains(half - TOLERANCE));
+        assert!(bbox.x.min > 10.0 - TOLERANCE && bbox.x.max < 10.0 + 2.0 * half + TOLERANCE);
+
+        // A ray through the rotated cube's far corner only hits because the
+        // box was rotated.
+        let ray = Ray::new(point3(10.0 + 2.0 * half - 0.01, 0.5, 5.0), -Vec3::Z, None);
+        assert!(instance.hit(&ray, Interval::UNIVERSE).is_some());
+    }
+}
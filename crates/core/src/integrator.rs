@@ -0,0 +1,830 @@
+//! Integrators: what a camera ray sees.
+//!
+//! An [`Integrator`] estimates the radiance `L_i` arriving along a ray from
+//! a [`Scene`]. [`Camera`](crate::camera::Camera) only generates rays and
+//! averages the estimates, so swapping the integrator changes what is
+//! rendered (full global illumination, direct light only, ambient
+//! occlusion, or a debug view) without touching the camera.
+
+use rand::prelude::Rng;
+use shared::random;
+
+use crate::prelude::{
+    Background,
+    Color3,
+    CosinePdf,
+    HitRecord,
+    Hittable,
+    HittablePdf,
+    Lobe,
+    MixturePdf,
+    Pdf,
+    Point3,
+    Ray,
+    Vec3,
+    interval,
+    power_heuristic,
+};
+
+/// Estimates the radiance arriving along a ray.
+pub trait Integrator: Send + Sync {
+    /// One sample of the radiance arriving at `ray.origin` from along
+    /// `ray`, drawing any random numbers it needs from `sampler`.
+    fn li(&self, ray: &Ray, scene: &Scene<'_>, sampler: &mut dyn Rng) -> Color3;
+}
+
+/// Everything an [`Integrator`] may look at.
+#[derive(Clone, Copy)]
+pub struct Scene<'a> {
+    /// All geometry, lights included.
+    pub world: &'a dyn Hittable,
+    /// The emitters to aim samples at, if the integrator samples lights.
+    /// Always [sampleable](Hittable::is_sampleable).
+    pub lights: Option<&'a dyn Hittable>,
+    /// Radiance of rays that escape `world`.
+    pub background: &'a Background,
+}
+
+impl<'a> Scene<'a> {
+    #[inline]
+    #[must_use]
+    pub const fn new(world: &'a dyn Hittable, background: &'a Background) -> Self {
+        Self { world, lights: None, background }
+    }
+
+    /// Aims samples at `lights`, unless they can't be sampled: then the
+    /// scene is rendered as if it had no lights, which is what sampling
+    /// them would come to.
+    #[inline]
+    #[must_use]
+    pub fn with_lights(self, lights: &'a dyn Hittable) -> Self {
+        Self { lights: lights.is_sampleable().then_some(lights), ..self }
+    }
+
+    /// The nearest surface along `ray`.
+    #[must_use]
+    pub fn hit(&self, ray: &Ray) -> Option<HitRecord> {
+        // t_min = 0.001 avoids "shadow acne": self-intersection due to the hit
+        // point floating slightly inside the surface.
+        self.world.hit(ray, interval(0.001, f64::INFINITY))
+    }
+
+    /// Emitted radiance at the first thing `ray` hits, or the background if
+    /// it escapes. What a shadow ray sees.
+    #[must_use]
+    pub fn emitted_along(&self, ray: &Ray) -> Color3 {
+        match self.hit(ray) {
+            Some(rec) => rec.material.emitted(rec.u, rec.v, rec.p),
+            None => self.background.value(ray.direction),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PathIntegrator
+// ---------------------------------------------------------------------------
+
+/// How a path tracer with lights finds them from diffuse surfaces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LightSampling {
+    /// Each diffuse bounce draws its one ray from an even mix of the
+    /// material's density and one aimed at the lights.
+    #[default]
+    Mixture,
+    /// Next-event estimation: each diffuse vertex sends a shadow ray to a
+    /// point sampled on the lights as well as a ray sampled from the
+    /// material, and weighs the light each one finds with the power
+    /// heuristic. Specular vertices only follow their ray.
+    NextEvent,
+}
+
+/// How a path is followed from the camera.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PathTracing {
+    /// One recursive call per bounce, as in the books, cut off at
+    /// [`PathIntegrator::max_depth`]. Light that needs more bounces is lost,
+    /// which darkens the image slightly. This is the books' estimator, so
+    /// renders converge to the books' images, though not sample for sample:
+    /// materials now scatter through their PDFs.
+    #[default]
+    Recursive,
+    /// A loop that carries the path's throughput, so depth costs no stack.
+    ///
+    /// With `roulette_depth: None` the path is still cut off at
+    /// [`PathIntegrator::max_depth`], and the result matches
+    /// [`Self::Recursive`]. With `Some(depth)`, `max_depth` is ignored: after
+    /// `depth` bounces each path survives with a probability that follows
+    /// its throughput (at most 0.95), and survivors are scaled up to make up
+    /// for the ones that stop. Paths then end with probability one without
+    /// any light going missing, so the estimate is unbiased.
+    Iterative { roulette_depth: Option<u32> },
+}
+
+/// The books' path tracer: full global illumination, one path per sample.
+///
+/// Without [`Scene::lights`] it computes the books' estimator and converges
+/// to the same images; with lights, diffuse bounces also aim at them as set
+/// by `light_sampling`.
+#[derive(Clone, Copy, Debug)]
+pub struct PathIntegrator {
+    /// Maximum ray-bounce depth. Not used by [`PathTracing::Iterative`]
+    /// paths with Russian roulette.
+    pub max_depth: u32,
+    pub light_sampling: LightSampling,
+    pub path_tracing: PathTracing,
+}
+
+impl Default for PathIntegrator {
+    fn default() -> Self {
+        Self {
+            max_depth: 10,
+            light_sampling: LightSampling::Mixture,
+            path_tracing: PathTracing::Recursive,
+        }
+    }
+}
+
+impl Integrator for PathIntegrator {
+    fn li(&self, ray: &Ray, scene: &Scene<'_>, sampler: &mut dyn Rng) -> Color3 {
+        match (self.path_tracing, scene.lights, self.light_sampling) {
+            (PathTracing::Iterative { roulette_depth }, ..) => {
+                self.ray_color_iterative(sampler, ray, scene, roulette_depth)
+            }
+            (PathTracing::Recursive, Some(lights), LightSampling::NextEvent) => {
+                Self::ray_color_nee(sampler, ray, self.max_depth, scene, lights, None)
+            }
+            (PathTracing::Recursive, ..) => Self::ray_color(sampler, ray, self.max_depth, scene),
+        }
+    }
+}
+
+impl PathIntegrator {
+    /// Recursively traces `ray` and returns the accumulated radiance.
+    ///
+    /// The recursion terminates either at `depth == 0` (absorb all light), at
+    /// a surface that does not scatter, or when a ray escapes to the
+    /// background. Every surface hit adds its own emission.
+    ///
+    /// Diffuse lobes are importance-sampled: the next direction comes from
+    /// the material's density, mixed half-and-half with one towards the
+    /// scene's lights when there are any, and its contribution is divided by
+    /// the density it was drawn with.
+    fn ray_color(rng: &mut dyn Rng, ray: &Ray, depth: u32, scene: &Scene<'_>) -> Color3 {
+        if depth == 0 {
+            return Color3::BLACK;
+        }
+
+        let Some(rec) = scene.hit(ray) else {
+            return scene.background.value(ray.direction);
+        };
+
+        let emitted = rec.material.emitted(rec.u, rec.v, rec.p);
+        let Some(srec) = rec.material.scatter_record(rng, ray, &rec) else {
+            return emitted;
+        };
+
+        let pdf = match srec.lobe {
+            Lobe::Specular(scattered) => {
+                let incoming = Self::ray_color(rng, &scattered, depth - 1, scene);
+                return emitted + srec.attenuation * incoming;
+            }
+            Lobe::Diffuse(pdf) => pdf,
+        };
+
+        let (direction, pdf_value) = sample_direction(rng, rec.p, pdf.as_ref(), scene.lights);
+        // The sample was drawn where the density vanishes, or underflowed.
+        if pdf_value <= 0.0 {
+            return emitted;
+        }
+
+        let scattered = Ray::new(rec.p, direction, Some(ray.time));
+        let scattering_pdf = rec.material.scatter_pdf(ray, &rec, &scattered);
+        let incoming = Self::ray_color(rng, &scattered, depth - 1, scene);
+        emitted + (scattering_pdf / pdf_value) * (srec.attenuation * incoming)
+    }
+
+    /// Like [`Self::ray_color`], with next-event estimation and multiple
+    /// importance sampling.
+    ///
+    /// `bsdf_pdf` is the density with which the previous vertex's material
+    /// drew `ray`, or `None` if that vertex was specular or is the camera.
+    /// Light that `ray` finds is weighted against the chance that the
+    /// previous vertex's shadow ray found it instead.
+    fn ray_color_nee(
+        rng: &mut dyn Rng,
+        ray: &Ray,
+        depth: u32,
+        scene: &Scene<'_>,
+        lights: &dyn Hittable,
+        bsdf_pdf: Option<f64>,
+    ) -> Color3 {
+        if depth == 0 {
+            return Color3::BLACK;
+        }
+
+        let weight = bsdf_pdf
+            .map_or(1.0, |pdf| power_heuristic(pdf, light_pdf(lights, ray.origin, ray.direction)));
+        let Some(rec) = scene.hit(ray) else {
+            return weight * scene.background.value(ray.direction);
+        };
+
+        let emitted = weight * rec.material.emitted(rec.u, rec.v, rec.p);
+        let Some(srec) = rec.material.scatter_record(rng, ray, &rec) else {
+            return emitted;
+        };
+
+        let pdf = match srec.lobe {
+            Lobe::Specular(scattered) => {
+                let incoming = Self::ray_color_nee(rng, &scattered, depth - 1, scene, lights, None);
+                return emitted + srec.attenuation * incoming;
+            }
+            Lobe::Diffuse(pdf) => pdf,
+        };
+
+        let direct = srec.attenuation * light_sample(rng, ray, &rec, pdf.as_ref(), scene, lights);
+
+        // BSDF sampling: carries on the path, and finds light too.
+        let direction = pdf.generate(rng);
+        let bsdf_pdf = pdf.value(direction);
+        if bsdf_pdf <= 0.0 {
+            return emitted + direct;
+        }
+        let scattered = Ray::new(rec.p, direction, Some(ray.time));
+        let scattering_pdf = rec.material.scatter_pdf(ray, &rec, &scattered);
+        let incoming =
+            Self::ray_color_nee(rng, &scattered, depth - 1, scene, lights, Some(bsdf_pdf));
+        emitted + direct + (scattering_pdf / bsdf_pdf) * (srec.attenuation * incoming)
+    }
+
+    /// Traces `ray` in a loop, keeping the product of every attenuation
+    /// along the path so far (its throughput) instead of a call stack.
+    ///
+    /// Shades exactly as [`Self::ray_color`] or [`Self::ray_color_nee`] do,
+    /// depending on `light_sampling`; see [`PathTracing::Iterative`] for how
+    /// `roulette_depth` ends paths.
+    fn ray_color_iterative(
+        &self,
+        rng: &mut dyn Rng,
+        ray: &Ray,
+        scene: &Scene<'_>,
+        roulette_depth: Option<u32>,
+    ) -> Color3 {
+        let next_event = scene.lights.filter(|_| self.light_sampling == LightSampling::NextEvent);
+        let mixed = scene.lights.filter(|_| next_event.is_none());
+
+        let mut radiance = Color3::BLACK;
+        let mut throughput = Color3::WHITE;
+        let mut ray = *ray;
+        // As in `ray_color_nee`: the density that drew `ray`, if any.
+        let mut bsdf_pdf = None;
+
+        for bounce in 0_u32.. {
+            if roulette_depth.is_none() && bounce == self.max_depth {
+                break;
+            }
+
+            let weight = match (next_event, bsdf_pdf) {
+                (Some(lights), Some(pdf)) => {
+                    power_heuristic(pdf, light_pdf(lights, ray.origin, ray.direction))
+                }
+                _ => 1.0,
+            };
+            let Some(rec) = scene.hit(&ray) else {
+                radiance += weight * (throughput * scene.background.value(ray.direction));
+                break;
+            };
+            radiance += weight * (throughput * rec.material.emitted(rec.u, rec.v, rec.p));
+            let Some(srec) = rec.material.scatter_record(rng, &ray, &rec) else {
+                break;
+            };
+
+            let (scattered, pdf_value) = match srec.lobe {
+                Lobe::Specular(scattered) => {
+                    throughput = throughput * srec.attenuation;
+                    (scattered, None)
+                }
+                Lobe::Diffuse(pdf) => {
+                    if let Some(lights) = next_event {
+                        let direct = light_sample(rng, &ray, &rec, pdf.as_ref(), scene, lights);
+                        radiance += throughput * srec.attenuation * direct;
+                    }
+                    let (direction, pdf_value) = sample_direction(rng, rec.p, pdf.as_ref(), mixed);
+                    if pdf_value <= 0.0 {
+                        break;
+                    }
+                    let scattered = Ray::new(rec.p, direction, Some(ray.time));
+                    let scattering_pdf = rec.material.scatter_pdf(&ray, &rec, &scattered);
+                    throughput = (scattering_pdf / pdf_value) * (throughput * srec.attenuation);
+                    (scattered, Some(pdf_value))
+                }
+            };
+            ray = scattered;
+            bsdf_pdf = pdf_value;
+
+            if let Some(depth) = roulette_depth
+                && bounce + 1 >= depth
+            {
+                let survival = throughput.r.max(throughput.g).max(throughput.b).min(0.95);
+                if random(rng) >= survival {
+                    break;
+                }
+                throughput = (1.0 / survival) * throughput;
+            }
+        }
+        radiance
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DirectLighting
+// ---------------------------------------------------------------------------
+
+/// Light that reaches the first diffuse surface straight from an emitter
+/// or the background, with no indirect bounces.
+///
+/// Specular surfaces are followed up to `max_depth` vertices to find it. At
+/// the diffuse surface one shadow ray towards [`Scene::lights`] and one ray
+/// drawn from the material are combined with the power heuristic; without
+/// lights only the material's ray is used.
+#[derive(Clone, Copy, Debug)]
+pub struct DirectLighting {
+    pub max_depth: u32,
+}
+
+impl Default for DirectLighting {
+    fn default() -> Self { Self { max_depth: 10 } }
+}
+
+impl Integrator for DirectLighting {
+    fn li(&self, ray: &Ray, scene: &Scene<'_>, sampler: &mut dyn Rng) -> Color3 {
+        let mut radiance = Color3::BLACK;
+        let mut throughput = Color3::WHITE;
+        let mut ray = *ray;
+
+        for _ in 0..self.max_depth {
+            let Some(rec) = scene.hit(&ray) else {
+                return radiance + throughput * scene.background.value(ray.direction);
+            };
+            radiance += throughput * rec.material.emitted(rec.u, rec.v, rec.p);
+            let Some(srec) = rec.material.scatter_record(sampler, &ray, &rec) else {
+                break;
+            };
+
+            let pdf = match srec.lobe {
+                Lobe::Specular(scattered) => {
+                    throughput = throughput * srec.attenuation;
+                    ray = scattered;
+                    continue;
+                }
+                Lobe::Diffuse(pdf) => pdf,
+            };
+
+            let mut direct = Color3::BLACK;
+            if let Some(lights) = scene.lights {
+                direct += light_sample(sampler, &ray, &rec, pdf.as_ref(), scene, lights);
+            }
+            let direction = pdf.generate(sampler);
+            let bsdf_pdf = pdf.value(direction);
+            if bsdf_pdf > 0.0 {
+                let scattered = Ray::new(rec.p, direction, Some(ray.time));
+                let scattering_pdf = rec.material.scatter_pdf(&ray, &rec, &scattered);
+                let weight = scene.lights.map_or(1.0, |lights| {
+                    power_heuristic(bsdf_pdf, light_pdf(lights, rec.p, direction))
+                });
+                direct += (weight * scattering_pdf / bsdf_pdf) * scene.emitted_along(&scattered);
+            }
+            return radiance + throughput * srec.attenuation * direct;
+        }
+        radiance
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AmbientOcclusion
+// ---------------------------------------------------------------------------
+
+/// How open the sky is above the first surface hit: white where nothing
+/// lies within `distance` of it, black where it is fully enclosed. Ignores
+/// materials and lights; rays that miss everything see the background.
+#[derive(Clone, Copy, Debug)]
+pub struct AmbientOcclusion {
+    /// Occluders further than this from the surface are ignored.
+    pub distance: f64,
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self { Self { distance: f64::INFINITY } }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn li(&self, ray: &Ray, scene: &Scene<'_>, sampler: &mut dyn Rng) -> Color3 {
+        let Some(rec) = scene.hit(ray) else {
+            return scene.background.value(ray.direction);
+        };
+        // Cosine-weighted, so the mean weighs each direction as a
+        // Lambertian surface would.
+        let direction = CosinePdf::new(rec.normal).generate(sampler);
+        let probe = Ray::new(rec.p, direction, Some(ray.time));
+        let occluded = scene.world.hit(&probe, interval(0.001, self.distance)).is_some();
+        if occluded { Color3::BLACK } else { Color3::WHITE }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DebugView
+// ---------------------------------------------------------------------------
+
+/// Shows one property of the first surface hit, for checking a scene's
+/// geometry and materials. Rays that miss everything are black.
+#[derive(Clone, Copy, Debug)]
+pub enum DebugView {
+    /// The shading normal, mapped from [-1, 1] to [0, 1] per axis.
+    Normals,
+    /// The material's attenuation, or its emission if it does not scatter.
+    Albedo,
+    /// Distance along the ray: white at the camera, fading to black at
+    /// `far`.
+    Depth { far: f64 },
+}
+
+impl Integrator for DebugView {
+    fn li(&self, ray: &Ray, scene: &Scene<'_>, sampler: &mut dyn Rng) -> Color3 {
+        let Some(rec) = scene.hit(ray) else {
+            return Color3::BLACK;
+        };
+        match *self {
+            Self::Normals => {
+                let n = 0.5 * (rec.normal + Vec3::ONE);
+                Color3::new(n.x, n.y, n.z)
+            }
+            Self::Albedo => match rec.material.scatter_record(sampler, ray, &rec) {
+                Some(srec) => srec.attenuation,
+                None => rec.material.emitted(rec.u, rec.v, rec.p),
+            },
+            Self::Depth { far } => {
+                let distance = rec.t * ray.direction.length();
+                Color3::splat(1.0 - (distance / far).clamp(0.0, 1.0))
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Shared sampling
+// ---------------------------------------------------------------------------
+
+/// Draws the next direction from a diffuse lobe's `pdf`, mixed half and
+/// half with one towards `lights` if there are any, and returns it with the
+/// density it was drawn with.
+fn sample_direction(
+    rng: &mut dyn Rng,
+    origin: Point3,
+    pdf: &dyn Pdf,
+    lights: Option<&dyn Hittable>,
+) -> (Vec3, f64) {
+    let light_pdf = lights.map(|lights| HittablePdf::new(lights, origin));
+    let mixture = light_pdf.as_ref().map(|light| MixturePdf::new(light, pdf));
+    let sampling: &dyn Pdf = match mixture {
+        Some(ref mixture) => mixture,
+        None => pdf,
+    };
+    let direction = sampling.generate(rng);
+    (direction, sampling.value(direction))
+}
+
+/// The light-sampling half of next-event estimation: sends one shadow ray
+/// from `rec` towards `lights` and returns the light it brings, weighted
+/// against the chance that `pdf` drew it, before the surface's attenuation.
+fn light_sample(
+    rng: &mut dyn Rng,
+    ray: &Ray,
+    rec: &HitRecord,
+    pdf: &dyn Pdf,
+    scene: &Scene<'_>,
+    lights: &dyn Hittable,
+) -> Color3 {
+    let Some(direction) = lights.random(rng, rec.p) else {
+        return Color3::BLACK;
+    };
+    let light_pdf = light_pdf(lights, rec.p, direction);
+    let shadow = Ray::new(rec.p, direction, Some(ray.time));
+    let scattering_pdf = rec.material.scatter_pdf(ray, rec, &shadow);
+    if light_pdf > 0.0 && scattering_pdf > 0.0 {
+        let weight = power_heuristic(light_pdf, pdf.value(direction));
+        (weight * scattering_pdf / light_pdf) * scene.emitted_along(&shadow)
+    } else {
+        Color3::BLACK
+    }
+}
+
+/// The density with which `lights` picks `direction` from `origin`.
+fn light_pdf(lights: &dyn Hittable, origin: Point3, direction: Vec3) -> f64 {
+    lights.pdf_value(origin, direction).unwrap_or(0.0)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::SeedableRng as _;
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::prelude::{
+        BvhNode,
+        DiffuseLight,
+        Hittables,
+        Lambertian,
+        Material,
+        Metal,
+        Quad,
+        ScatterRecord,
+        Sphere,
+        TriangleMesh,
+        color,
+        point3,
+        vec3,
+    };
+
+    const BLACK: Color3 = Color3::BLACK;
+
+    /// A white floor under a small square light, and the lights alone.
+    fn lamp_scene() -> (Hittables, Hittables) {
+        let light: Arc<dyn Material> = Arc::new(DiffuseLight::new(color(50.0, 50.0, 50.0)));
+        lamp_scene_with(Arc::new(Quad::new(
+            point3(-0.1, 1.0, -0.1),
+            vec3(0.2, 0.0, 0.0),
+            vec3(0.0, 0.0, 0.2),
+            light,
+        )))
+    }
+
+    /// A white floor under `lamp`, and the lamp alone.
+    fn lamp_scene_with(lamp: Arc<dyn Hittable>) -> (Hittables, Hittables) {
+        let white: Arc<dyn Material> = Arc::new(Lambertian::new(color(0.8, 0.8, 0.8)));
+        let floor: Arc<dyn Hittable> =
+            Arc::new(Quad::new(point3(-5, 0, -5), vec3(0, 0, 10), vec3(10, 0, 0), white));
+        let world: Hittables = [floor, Arc::clone(&lamp)].into_iter().collect();
+        let lights: Hittables = [lamp].into_iter().collect();
+        (world, lights)
+    }
+
+    /// Mean and variance of the red channel of `n` samples along `ray`.
+    fn estimate(
+        integrator: &dyn Integrator,
+        scene: &Scene<'_>,
+        rng: &mut dyn Rng,
+        ray: &Ray,
+        n: usize,
+    ) -> (f64, f64) {
+        let samples: Vec<f64> =
+            core::iter::repeat_with(|| integrator.li(ray, scene, rng).r).take(n).collect();
+        let count = f64::from(u32::try_from(n).unwrap());
+        let mean = samples.iter().sum::<f64>() / count;
+        let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count;
+        (mean, variance)
+    }
+
+    #[test]
+    fn scenario_light_sampling_agrees_and_converges_faster() {
+        let (world, lights) = lamp_scene();
+        let background = Background::Solid(BLACK);
+        let unlit = Scene::new(&world, &background);
+        let lit = unlit.with_lights(&lights);
+        let path = PathIntegrator { max_depth: 3, ..PathIntegrator::default() };
+        let ray = Ray::new(point3(0.3, 0.5, 0.0), vec3(0, -1, 0), None);
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let (sampled, sampled_variance) = estimate(&path, &lit, &mut rng, &ray, 20_000);
+        let (brute, brute_variance) = estimate(&path, &unlit, &mut rng, &ray, 200_000);
+        assert!((sampled - brute).abs() < 0.08 * brute, "{sampled} vs {brute}");
+        assert!(sampled_variance * 10.0 < brute_variance);
+    }
+
+    #[test]
+    fn scenario_mesh_lights_and_lights_that_cannot_be_sampled() {
+        // The lamp as an emissive two-triangle mesh, as loaded from a file.
+        let light: Arc<dyn Material> = Arc::new(DiffuseLight::new(color(50.0, 50.0, 50.0)));
+        let corners = vec![
+            point3(-0.1, 1.0, -0.1),
+            point3(0.1, 1.0, -0.1),
+            point3(0.1, 1.0, 0.1),
+            point3(-0.1, 1.0, 0.1),
+        ];
+        let lamp: Arc<dyn Hittable> =
+            Arc::new(TriangleMesh::new(corners, vec![[0, 1, 2], [0, 2, 3]], light));
+        let (world, lights) = lamp_scene_with(lamp);
+        let background = Background::Solid(BLACK);
+        let unlit = Scene::new(&world, &background);
+        let path = PathIntegrator { max_depth: 3, ..PathIntegrator::default() };
+        let ray = Ray::new(point3(0.3, 0.5, 0.0), vec3(0, -1, 0), None);
+        let mut rng = StdRng::seed_from_u64(19);
+
+        let (brute, brute_variance) = estimate(&path, &unlit, &mut rng, &ray, 200_000);
+        let (sampled, sampled_variance) =
+            estimate(&path, &unlit.with_lights(&lights), &mut rng, &ray, 20_000);
+        assert!((sampled - brute).abs() < 0.08 * brute, "{sampled} vs {brute}");
+        assert!(sampled_variance * 10.0 < brute_variance);
+
+        // Behind a BVH the lamp can't be sampled, so only the material's
+        // density is used: the same answer, just no faster.
+        let hidden = BvhNode::new(lights);
+        let fallback_scene = unlit.with_lights(&hidden);
+        assert!(fallback_scene.lights.is_none());
+        let (fallback, _) = estimate(&path, &fallback_scene, &mut rng, &ray, 200_000);
+        assert!((fallback - brute).abs() < 0.08 * brute, "{fallback} vs {brute}");
+    }
+
+    #[test]
+    fn scenario_next_event_estimation_agrees_and_converges_faster() {
+        let (world, lights) = lamp_scene();
+        let background = Background::Solid(BLACK);
+        let unlit = Scene::new(&world, &background);
+        let lit = unlit.with_lights(&lights);
+        let mixture = PathIntegrator { max_depth: 3, ..PathIntegrator::default() };
+        let nee = PathIntegrator { light_sampling: LightSampling::NextEvent, ..mixture };
+        let ray = Ray::new(point3(0.3, 0.5, 0.0), vec3(0, -1, 0), None);
+        let mut rng = StdRng::seed_from_u64(12);
+
+        let (sampled, sampled_variance) = estimate(&nee, &lit, &mut rng, &ray, 20_000);
+        let (brute, _) = estimate(&nee, &unlit, &mut rng, &ray, 200_000);
+        let iterative =
+            PathIntegrator { path_tracing: PathTracing::Iterative { roulette_depth: None }, ..nee };
+        let (looped, _) = estimate(&iterative, &lit, &mut rng, &ray, 20_000);
+        let (_, mixture_variance) = estimate(&mixture, &lit, &mut rng, &ray, 20_000);
+        assert!((sampled - brute).abs() < 0.08 * brute, "{sampled} vs {brute}");
+        assert!((looped - brute).abs() < 0.08 * brute, "{looped} vs {brute}");
+        assert!(sampled_variance < mixture_variance, "{sampled_variance} vs {mixture_variance}");
+    }
+
+    #[test]
+    fn scenario_perfect_mirror_is_a_delta_lobe() {
+        // A mirror facing a light: with no fuzz there is only one direction
+        // to follow, so every sample sees exactly albedo × emission.
+        let (_, lights) = lamp_scene();
+        let albedo = color(0.9, 0.5, 0.25);
+        let mirror: Arc<dyn Material> = Arc::new(Metal::new(albedo, 0.0));
+        let floor: Arc<dyn Hittable> =
+            Arc::new(Quad::new(point3(-5, 0, -5), vec3(0, 0, 10), vec3(10, 0, 0), mirror));
+        let world: Hittables = [floor, Arc::new(lights.clone())].into_iter().collect();
+        let background = Background::Solid(BLACK);
+        let scene = Scene::new(&world, &background).with_lights(&lights);
+
+        let nee = PathIntegrator { light_sampling: LightSampling::NextEvent, ..Default::default() };
+        let ray = Ray::new(point3(0.0, 0.5, 0.0), vec3(0, -1, 0), None);
+        let mut rng = StdRng::seed_from_u64(13);
+        for _ in 0..100 {
+            assert_eq!(nee.li(&ray, &scene, &mut rng), albedo * color(50.0, 50.0, 50.0));
+        }
+    }
+
+    /// A diffuse surface that also glows, to build a closed furnace.
+    struct Glowing(Lambertian);
+
+    impl Material for Glowing {
+        fn scatter(
+            &self,
+            rng: &mut dyn Rng,
+            ray_in: &Ray,
+            rec: &HitRecord,
+        ) -> Option<(Color3, Ray)> {
+            self.0.scatter(rng, ray_in, rec)
+        }
+
+        fn scatter_record(
+            &self,
+            rng: &mut dyn Rng,
+            ray_in: &Ray,
+            rec: &HitRecord,
+        ) -> Option<ScatterRecord> {
+            self.0.scatter_record(rng, ray_in, rec)
+        }
+
+        fn scatter_pdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+            self.0.scatter_pdf(ray_in, rec, scattered)
+        }
+
+        fn emitted(&self, _: f64, _: f64, _: Point3) -> Color3 { color(1.0, 1.0, 1.0) }
+    }
+
+    /// A closed sphere that glows with 1 and reflects half: radiance
+    /// L = 1 + L / 2 everywhere inside, so L = 2 after infinitely many
+    /// bounces, and 1 + 1/2 + 1/4 = 1.75 after three.
+    fn furnace() -> Hittables {
+        let material: Arc<dyn Material> = Arc::new(Glowing(Lambertian::new(color(0.5, 0.5, 0.5))));
+        Hittables::from(vec![Sphere::new(Point3::ZERO, None, 10.0, material)])
+    }
+
+    #[test]
+    fn scenario_iterative_matches_recursive() {
+        let world = furnace();
+        let background = Background::Solid(BLACK);
+        let scene = Scene::new(&world, &background);
+        let ray = Ray::new(Point3::ZERO, vec3(1, 2, 3), None);
+        let mut rng = StdRng::seed_from_u64(14);
+        for path_tracing in
+            [PathTracing::Recursive, PathTracing::Iterative { roulette_depth: None }]
+        {
+            let path = PathIntegrator { max_depth: 3, path_tracing, ..Default::default() };
+            for _ in 0..100 {
+                shared::assert_fuzzy_eq!(path.li(&ray, &scene, &mut rng).r, 1.75);
+            }
+        }
+    }
+
+    #[test]
+    fn scenario_russian_roulette_removes_the_depth_bias() {
+        let world = furnace();
+        let background = Background::Solid(BLACK);
+        let scene = Scene::new(&world, &background);
+        let path = PathIntegrator {
+            max_depth: 3,
+            path_tracing: PathTracing::Iterative { roulette_depth: Some(2) },
+            ..Default::default()
+        };
+        let ray = Ray::new(Point3::ZERO, vec3(1, 2, 3), None);
+        let mut rng = StdRng::seed_from_u64(15);
+        let (mean, _) = estimate(&path, &scene, &mut rng, &ray, 50_000);
+        assert!((mean - 2.0).abs() < 0.02, "{mean}");
+    }
+
+    #[test]
+    fn scenario_direct_lighting() {
+        // Inside the furnace: the wall's own glow plus one bounce of it.
+        let world = furnace();
+        let background = Background::Solid(BLACK);
+        let scene = Scene::new(&world, &background);
+        let ray = Ray::new(Point3::ZERO, vec3(1, 2, 3), None);
+        let mut rng = StdRng::seed_from_u64(16);
+        for _ in 0..100 {
+            shared::assert_fuzzy_eq!(DirectLighting::default().li(&ray, &scene, &mut rng).r, 1.5);
+        }
+
+        // Under the lamp all light is direct, so it agrees with the full
+        // path tracer, with or without light sampling.
+        let (world, lights) = lamp_scene();
+        let unlit = Scene::new(&world, &background);
+        let lit = unlit.with_lights(&lights);
+        let ray = Ray::new(point3(0.3, 0.5, 0.0), vec3(0, -1, 0), None);
+        let (brute, _) = estimate(&PathIntegrator::default(), &unlit, &mut rng, &ray, 200_000);
+        let (direct, _) = estimate(&DirectLighting::default(), &lit, &mut rng, &ray, 20_000);
+        assert!((direct - brute).abs() < 0.08 * brute, "{direct} vs {brute}");
+        let (direct, _) = estimate(&DirectLighting::default(), &unlit, &mut rng, &ray, 200_000);
+        assert!((direct - brute).abs() < 0.08 * brute, "{direct} vs {brute}");
+    }
+
+    #[test]
+    fn scenario_ambient_occlusion() {
+        // A floor with a ceiling one unit above it.
+        let grey: Arc<dyn Material> = Arc::new(Lambertian::new(color(0.5, 0.5, 0.5)));
+        let floor: Arc<dyn Hittable> = Arc::new(Quad::new(
+            point3(-1e4, 0, -1e4),
+            vec3(0, 0, 2e4),
+            vec3(2e4, 0, 0),
+            Arc::clone(&grey),
+        ));
+        let ceiling: Arc<dyn Hittable> =
+            Arc::new(Quad::new(point3(-1e4, 1, -1e4), vec3(0, 0, 2e4), vec3(2e4, 0, 0), grey));
+        let background = Background::Solid(color(0.1, 0.2, 0.3));
+        let open: Hittables = [Arc::clone(&floor)].into_iter().collect();
+        let closed: Hittables = [floor, ceiling].into_iter().collect();
+
+        let down = Ray::new(point3(0.0, 0.5, 0.0), vec3(0, -1, 0), None);
+        let up = Ray::new(point3(0.0, 0.5, 0.0), vec3(0, 1, 0), None);
+        let mut rng = StdRng::seed_from_u64(17);
+        let ao = AmbientOcclusion::default();
+        let near = AmbientOcclusion { distance: 0.5 };
+        for _ in 0..100 {
+            assert_eq!(ao.li(&down, &Scene::new(&open, &background), &mut rng), Color3::WHITE);
+            assert_eq!(ao.li(&up, &Scene::new(&open, &background), &mut rng), color(0.1, 0.2, 0.3));
+            assert_eq!(ao.li(&down, &Scene::new(&closed, &background), &mut rng), BLACK);
+            assert_eq!(near.li(&down, &Scene::new(&closed, &background), &mut rng), Color3::WHITE);
+        }
+    }
+
+    #[test]
+    fn scenario_debug_views() {
+        let material: Arc<dyn Material> = Arc::new(Lambertian::new(color(0.2, 0.4, 0.6)));
+        let world = Hittables::from(vec![Quad::new(
+            point3(-5, 0, -5),
+            vec3(0, 0, 10),
+            vec3(10, 0, 0),
+            material,
+        )]);
+        let background = Background::Solid(color(1.0, 1.0, 1.0));
+        let scene = Scene::new(&world, &background);
+        let ray = Ray::new(point3(0, 2, 0), vec3(0, -2, 0), None);
+        let mut rng = StdRng::seed_from_u64(18);
+
+        assert_eq!(DebugView::Normals.li(&ray, &scene, &mut rng), color(0.5, 1.0, 0.5));
+        assert_eq!(DebugView::Albedo.li(&ray, &scene, &mut rng), color(0.2, 0.4, 0.6));
+        let depth = DebugView::Depth { far: 8.0 }.li(&ray, &scene, &mut rng);
+        shared::assert_fuzzy_eq!(depth.r, 0.75);
+        let away = Ray::new(point3(0, 2, 0), vec3(0, 1, 0), None);
+        assert_eq!(DebugView::Normals.li(&away, &scene, &mut rng), BLACK);
+    }
+}
@@ -12,13 +12,26 @@
 #![feature(impl_trait_in_assoc_type)]
 
 pub mod aabb;
+pub mod animation;
 pub mod axis;
+pub mod background;
+pub mod bvh;
 pub mod camera;
 pub mod color;
 pub mod geometry;
 pub mod hittable;
+pub mod image;
+pub mod instance;
+pub mod integrator;
 pub mod interval;
 pub mod material;
+pub mod medium;
+pub mod mesh;
+pub mod pdf;
+pub mod perlin;
 pub mod prelude;
+pub mod quad;
 pub mod ray;
 pub mod sphere;
+pub mod texture;
+pub mod triangle;
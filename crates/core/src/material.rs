@@ -1,7 +1,21 @@
+use core::f64::consts::PI;
+use std::sync::Arc;
+
 use rand::prelude::Rng;
 use shared::random;
 
-use crate::prelude::{Color3, HitRecord, Ray, Vec3};
+use crate::prelude::{
+    Color3,
+    CosinePdf,
+    HitRecord,
+    Pdf,
+    Point3,
+    Ray,
+    SolidColor,
+    SpherePdf,
+    Texture,
+    Vec3,
+};
 
 /// A material decides whether (and how) an incoming ray scatters.
 ///
@@ -16,6 +30,46 @@ use crate::prelude::{Color3, HitRecord, Ray, Vec3};
 /// would return `None`.
 pub trait Material: Send + Sync {
     fn scatter(&self, rng: &mut dyn Rng, ray_in: &Ray, rec: &HitRecord) -> Option<(Color3, Ray)>;
+
+    /// Like [`Material::scatter`], but a material may describe its
+    /// scattering as a density instead of a single ray, so the integrator
+    /// can mix in other densities such as one aimed at the lights. By
+    /// default the ray from `scatter` is returned as a specular lobe.
+    fn scatter_record(
+        &self,
+        rng: &mut dyn Rng,
+        ray_in: &Ray,
+        rec: &HitRecord,
+    ) -> Option<ScatterRecord> {
+        let (attenuation, scattered) = self.scatter(rng, ray_in, rec)?;
+        Some(ScatterRecord { attenuation, lobe: Lobe::Specular(scattered) })
+    }
+
+    /// The density with which this material scatters towards `scattered`,
+    /// for the [`Lobe::Diffuse`] lobe of its [`ScatterRecord`].
+    fn scatter_pdf(&self, _ray_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> f64 { 0.0 }
+
+    /// Radiance emitted at surface coordinates `(u, v)` and point `p`.
+    /// Black for everything except lights.
+    fn emitted(&self, _u: f64, _v: f64, _p: Point3) -> Color3 { Color3::BLACK }
+}
+
+/// How a surface scatters, as returned by [`Material::scatter_record`].
+pub struct ScatterRecord {
+    pub attenuation: Color3,
+    pub lobe: Lobe,
+}
+
+pub enum Lobe {
+    /// A single ray to follow, whose density cannot be evaluated. This is
+    /// how delta lobes such as a perfect mirror ([`Metal`] with zero fuzz)
+    /// and [`Dielectric`] report themselves; the attenuation already
+    /// accounts for how the ray was chosen, so integrators follow it as is
+    /// and never aim light samples through it.
+    Specular(Ray),
+    /// A density to draw directions from. A direction drawn from some
+    /// density `q` carries attenuation × [`Material::scatter_pdf`] / `q`.
+    Diffuse(Box<dyn Pdf>),
 }
 
 // ---------------------------------------------------------------------------
@@ -25,16 +79,28 @@ pub trait Material: Send + Sync {
 /// Perfectly diffuse (Lambertian) surface.
 ///
 /// Scatters in a direction near the surface normal with cosine weighting,
-/// giving physically correct attenuation without an explicit PDF term.
-#[derive(Clone, Copy, Debug)]
+/// giving physically correct attenuation without an explicit PDF term. The
+/// albedo is looked up from `texture` at the hit's `(u, v)`, tinted by the
+/// hit's vertex colour if it has one.
+#[derive(Clone, Debug)]
 pub struct Lambertian {
-    pub albedo: Color3,
+    pub texture: Arc<dyn Texture>,
 }
 
 impl Lambertian {
+    /// A uniformly coloured surface.
+    #[inline]
+    #[must_use]
+    pub fn new(albedo: Color3) -> Self { Self::with_texture(Arc::new(SolidColor::new(albedo))) }
+
     #[inline]
     #[must_use]
-    pub const fn new(albedo: Color3) -> Self { Self { albedo } }
+    pub const fn with_texture(texture: Arc<dyn Texture>) -> Self { Self { texture } }
+
+    fn albedo(&self, rec: &HitRecord) -> Color3 {
+        let albedo = self.texture.value(rec.u, rec.v, rec.p);
+        rec.color.map_or(albedo, |tint| tint * albedo)
+    }
 }
 
 impl Material for Lambertian {
@@ -48,7 +114,17 @@ impl Material for Lambertian {
         };
 
         let scattered = Ray::new(rec.p, direction, Some(ray_in.time));
-        Some((self.albedo, scattered))
+        Some((self.albedo(rec), scattered))
+    }
+
+    fn scatter_record(&self, _: &mut dyn Rng, _: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        let lobe = Lobe::Diffuse(Box::new(CosinePdf::new(rec.normal)));
+        Some(ScatterRecord { attenuation: self.albedo(rec), lobe })
+    }
+
+    fn scatter_pdf(&self, _: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        let cosine = rec.normal.dot(scattered.direction.unit());
+        (cosine / PI).max(0.0)
     }
 }
 
@@ -129,6 +205,70 @@ impl Material for Dielectric {
     }
 }
 
+// ---------------------------------------------------------------------------
+// DiffuseLight (emitter)
+// ---------------------------------------------------------------------------
+
+/// A surface that emits light uniformly in every direction and scatters
+/// nothing. The emitted radiance is looked up from `texture`.
+#[derive(Clone, Debug)]
+pub struct DiffuseLight {
+    pub texture: Arc<dyn Texture>,
+}
+
+impl DiffuseLight {
+    /// A uniformly bright emitter.
+    #[inline]
+    #[must_use]
+    pub fn new(emit: Color3) -> Self { Self::with_texture(Arc::new(SolidColor::new(emit))) }
+
+    #[inline]
+    #[must_use]
+    pub const fn with_texture(texture: Arc<dyn Texture>) -> Self { Self { texture } }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _: &mut dyn Rng, _: &Ray, _: &HitRecord) -> Option<(Color3, Ray)> { None }
+
+    fn emitted(&self, u: f64, v: f64, p: Point3) -> Color3 { self.texture.value(u, v, p) }
+}
+
+// ---------------------------------------------------------------------------
+// Isotropic (phase function)
+// ---------------------------------------------------------------------------
+
+/// The phase function of a participating medium: scatters uniformly over
+/// the whole sphere of directions, with no regard for the normal. The
+/// albedo is looked up from `texture`.
+#[derive(Clone, Debug)]
+pub struct Isotropic {
+    pub texture: Arc<dyn Texture>,
+}
+
+impl Isotropic {
+    #[inline]
+    #[must_use]
+    pub fn new(albedo: Color3) -> Self { Self::with_texture(Arc::new(SolidColor::new(albedo))) }
+
+    #[inline]
+    #[must_use]
+    pub const fn with_texture(texture: Arc<dyn Texture>) -> Self { Self { texture } }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, rng: &mut dyn Rng, ray_in: &Ray, rec: &HitRecord) -> Option<(Color3, Ray)> {
+        let scattered = Ray::new(rec.p, Vec3::random_unit(rng), Some(ray_in.time));
+        Some((self.texture.value(rec.u, rec.v, rec.p), scattered))
+    }
+
+    fn scatter_record(&self, _: &mut dyn Rng, _: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        let attenuation = self.texture.value(rec.u, rec.v, rec.p);
+        Some(ScatterRecord { attenuation, lobe: Lobe::Diffuse(Box::new(SpherePdf)) })
+    }
+
+    fn scatter_pdf(&self, _: &Ray, _: &HitRecord, _: &Ray) -> f64 { 1.0 / (4.0 * PI) }
+}
+
 // ---------------------------------------------------------------------------
 // Schlick reflectance approximation
 // ---------------------------------------------------------------------------
@@ -0,0 +1,532 @@
+//! Heterogeneous media whose density comes from a voxel grid.
+//!
+//! Grids are stored in one of two formats, told apart by their first bytes:
+//!
+//! - **Binary**: the magic `VGRD`, the dimensions as three little-endian
+//!   `u32`s, then one little-endian `f32` per voxel.
+//! - **Text**: a `dims nx ny nz` line followed by the voxel values,
+//!   whitespace-separated over any number of lines. `#` starts a comment.
+//!
+//! Either way the values run with `x` fastest, then `y`, then `z`.
+
+use core::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fs, io};
+
+use rand::prelude::Rng;
+use shared::random;
+
+use super::scattering;
+use crate::prelude::{
+    AABB,
+    Color3,
+    HitRecord,
+    Hittable,
+    Interval,
+    Isotropic,
+    Material,
+    Point3,
+    Ray,
+};
+
+const MAGIC: &[u8; 4] = b"VGRD";
+
+// ---------------------------------------------------------------------------
+// GridError
+// ---------------------------------------------------------------------------
+
+/// Why a density grid could not be loaded.
+#[derive(Debug)]
+pub enum GridError {
+    /// Nothing exists at the path.
+    NotFound(PathBuf),
+    /// The file could not be read, or is not a valid binary grid.
+    Io { path: PathBuf, source: io::Error },
+    /// The text is malformed. `line` is 1-based; `path` is empty for
+    /// in-memory sources.
+    Parse { path: PathBuf, line: usize, message: String },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::NotFound(ref path) => write!(f, "density grid '{}' not found", path.display()),
+            Self::Io { ref path, ref source } => {
+                write!(f, "cannot read density grid '{}': {source}", path.display())
+            }
+            Self::Parse { ref path, line, ref message } if path.as_os_str().is_empty() => {
+                write!(f, "line {line}: {message}")
+            }
+            Self::Parse { ref path, line, ref message } => {
+                write!(f, "{}:{line}: {message}", path.display())
+            }
+        }
+    }
+}
+
+impl core::error::Error for GridError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match *self {
+            Self::Io { ref source, .. } => Some(source),
+            Self::NotFound(_) | Self::Parse { .. } => None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DensityGrid
+// ---------------------------------------------------------------------------
+
+/// A dense 3-D grid of non-negative densities over the unit cube.
+///
+/// Each value sits at the centre of its voxel, and [`DensityGrid::sample`]
+/// interpolates between them trilinearly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DensityGrid {
+    dims: [u32; 3],
+    values: Vec<f64>,
+    max: f64,
+}
+
+impl DensityGrid {
+    /// # Panics
+    ///
+    /// If a dimension is zero, if there is not exactly one value per voxel,
+    /// or if a value is negative or not finite.
+    #[must_use]
+    pub fn new(dims: [u32; 3], values: Vec<f64>) -> Self {
+        assert!(dims.iter().all(|&n| n > 0), "grid dimensions must be non-zero");
+        assert_eq!(Some(values.len()), voxel_count(dims), "one value per voxel");
+        assert!(
+            values.iter().all(|v| v.is_finite() && *v >= 0.0),
+            "densities must be finite and non-negative"
+        );
+        let max = values.iter().copied().fold(0.0, f64::max);
+        Self { dims, values, max }
+    }
+
+    /// Reads a grid file in either format.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, GridError> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|source| match source.kind() {
+            io::ErrorKind::NotFound => GridError::NotFound(path.to_path_buf()),
+            _ => GridError::Io { path: path.to_path_buf(), source },
+        })?;
+        parse(&data, path)
+    }
+
+    /// Reads a grid from memory, in either format.
+    pub fn parse(data: &[u8]) -> Result<Self, GridError> { parse(data, Path::new("")) }
+
+    /// The grid in the binary format.
+    #[must_use]
+    #[expect(clippy::little_endian_bytes, reason = "binary grids are little-endian")]
+    #[expect(clippy::cast_possible_truncation, clippy::as_conversions)]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(self.dims.iter().flat_map(|n| n.to_le_bytes()));
+        bytes.extend(self.values.iter().flat_map(|&v| (v as f32).to_le_bytes()));
+        bytes
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn dims(&self) -> [u32; 3] { self.dims }
+
+    #[inline]
+    #[must_use]
+    pub fn values(&self) -> &[f64] { &self.values }
+
+    /// The largest voxel value.
+    #[inline]
+    #[must_use]
+    pub const fn max(&self) -> f64 { self.max }
+
+    /// The density at `p` in the unit cube, interpolated trilinearly between
+    /// voxel centres. Zero outside the cube.
+    #[must_use]
+    pub fn sample(&self, p: Point3) -> f64 {
+        if ![p.x, p.y, p.z].iter().all(|c| (0.0..=1.0).contains(c)) {
+            return 0.0;
+        }
+        let [nx, ny, nz] = self.dims;
+        let (x0, x1, fx) = cell(p.x, nx);
+        let (y0, y1, fy) = cell(p.y, ny);
+        let (z0, z1, fz) = cell(p.z, nz);
+
+        let lerp = |a: f64, b: f64, f: f64| (1.0 - f) * a + f * b;
+        let row = |y: u32, z: u32| lerp(self.voxel(x0, y, z), self.voxel(x1, y, z), fx);
+        let plane = |z: u32| lerp(row(y0, z), row(y1, z), fy);
+        lerp(plane(z0), plane(z1), fz)
+    }
+
+    fn voxel(&self, x: u32, y: u32, z: u32) -> f64 {
+        let [nx, ny, _] = self.dims.map(to_usize);
+        let index = to_usize(x) + nx * (to_usize(y) + ny * to_usize(z));
+        self.values.get(index).copied().unwrap_or(0.0)
+    }
+}
+
+/// The two voxels either side of unit coordinate `c` along an axis of `n`
+/// voxels, and how far `c` lies between their centres.
+fn cell(c: f64, n: u32) -> (u32, u32, f64) {
+    let x = (c * f64::from(n) - 0.5).clamp(0.0, f64::from(n - 1));
+    let lower = to_u32(x.floor());
+    (lower, (lower + 1).min(n - 1), x - x.floor())
+}
+
+fn voxel_count(dims: [u32; 3]) -> Option<usize> {
+    dims.iter().try_fold(1_usize, |total, &n| total.checked_mul(to_usize(n)))
+}
+
+fn to_usize(i: u32) -> usize { usize::try_from(i).unwrap_or(usize::MAX) }
+
+#[expect(clippy::as_conversions, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+const fn to_u32(v: f64) -> u32 { v as u32 }
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+fn parse(data: &[u8], path: &Path) -> Result<DensityGrid, GridError> {
+    match data.strip_prefix(MAGIC) {
+        Some(body) => parse_binary(body, path),
+        None => parse_text(data, path),
+    }
+}
+
+#[expect(clippy::little_endian_bytes, reason = "binary grids are little-endian")]
+fn parse_binary(body: &[u8], path: &Path) -> Result<DensityGrid, GridError> {
+    let invalid = |message: String| GridError::Io {
+        path: path.to_path_buf(),
+        source: io::Error::new(io::ErrorKind::InvalidData, message),
+    };
+    let (header, values) =
+        body.split_at_checked(12).ok_or_else(|| invalid("truncated header".into()))?;
+    let mut words =
+        header.chunks_exact(4).map(|w| u32::from_le_bytes(w.try_into().unwrap_or_default()));
+    let dims = [words.next(), words.next(), words.next()].map(Option::unwrap_or_default);
+    let count = check_dims(dims).map_err(invalid)?;
+
+    if Some(values.len()) != count.checked_mul(4) {
+        return Err(invalid(format!(
+            "expected {count} voxels, found {} bytes of data",
+            values.len()
+        )));
+    }
+    let values: Vec<f64> = values
+        .chunks_exact(4)
+        .map(|v| f64::from(f32::from_le_bytes(v.try_into().unwrap_or_default())))
+        .collect();
+    if let Some(bad) = values.iter().position(|v| !v.is_finite() || *v < 0.0) {
+        return Err(invalid(format!("voxel {bad} is not a finite, non-negative density")));
+    }
+    Ok(DensityGrid::new(dims, values))
+}
+
+fn parse_text(data: &[u8], path: &Path) -> Result<DensityGrid, GridError> {
+    let text = String::from_utf8_lossy(data);
+    let error =
+        |line: usize, message: String| GridError::Parse { path: path.to_path_buf(), line, message };
+
+    let mut dims: Option<([u32; 3], usize)> = None;
+    let mut values = Vec::new();
+    let mut last = 0;
+    for (line, content) in (1..).zip(text.lines()) {
+        last = line;
+        let content = content.split_once('#').map_or(content, |(before, _)| before);
+        let mut tokens = content.split_whitespace().peekable();
+        if tokens.peek().is_none() {
+            continue;
+        }
+
+        let Some((_, count)) = dims else {
+            if tokens.next() != Some("dims") {
+                return Err(error(line, String::from("expected 'dims nx ny nz' first")));
+            }
+            let mut dim = || {
+                let token = tokens.next().unwrap_or_default();
+                token
+                    .parse::<u32>()
+                    .map_err(|e| error(line, format!("bad dimension '{token}': {e}")))
+            };
+            let parsed = [dim()?, dim()?, dim()?];
+            let count = check_dims(parsed).map_err(|message| error(line, message))?;
+            dims = Some((parsed, count));
+            continue;
+        };
+
+        for token in tokens {
+            let value = token
+                .parse::<f64>()
+                .map_err(|e| error(line, format!("expected a density, found '{token}': {e}")))?;
+            if !value.is_finite() || value < 0.0 {
+                return Err(error(line, format!("density {token} is not finite and non-negative")));
+            }
+            if values.len() == count {
+                return Err(error(line, format!("more than the {count} voxels declared")));
+            }
+            values.push(value);
+        }
+    }
+
+    let Some((dims, count)) = dims else {
+        return Err(error(last.max(1), String::from("missing 'dims nx ny nz'")));
+    };
+    if values.len() != count {
+        return Err(error(last, format!("expected {count} voxels, found {}", values.len())));
+    }
+    Ok(DensityGrid::new(dims, values))
+}
+
+/// The voxel count of `dims`, if every dimension is non-zero and the total
+/// fits in memory.
+fn check_dims(dims: [u32; 3]) -> Result<usize, String> {
+    let [nx, ny, nz] = dims;
+    if dims.contains(&0) {
+        return Err(format!("grid {nx}×{ny}×{nz} has no voxels"));
+    }
+    voxel_count(dims).ok_or_else(|| format!("grid {nx}×{ny}×{nz} is too large"))
+}
+
+// ---------------------------------------------------------------------------
+// GridMedium
+// ---------------------------------------------------------------------------
+
+/// A medium whose density varies through `bounds` as given by a
+/// [`DensityGrid`], such as a cloud or a smoke simulation.
+///
+/// Free paths are sampled by delta tracking: tentative collisions are drawn
+/// against the constant majorant, the grid's maximum density, and each is
+/// kept with probability density / majorant. [`GridMedium::transmittance`]
+/// estimates the fraction of light that gets through by ratio tracking.
+pub struct GridMedium {
+    grid: Arc<DensityGrid>,
+    bounds: AABB,
+    /// Multiplies every grid value.
+    scale: f64,
+    /// An upper bound on the density anywhere in the medium.
+    majorant: f64,
+    phase_function: Arc<dyn Material>,
+}
+
+impl GridMedium {
+    /// A medium that scatters with an [`Isotropic`] phase function of the
+    /// given albedo. The grid is stretched to fill `bounds` and its values
+    /// multiplied by `scale`.
+    #[must_use]
+    pub fn new(grid: Arc<DensityGrid>, bounds: AABB, scale: f64, albedo: Color3) -> Self {
+        Self::with_phase_function(grid, bounds, scale, Arc::new(Isotropic::new(albedo)))
+    }
+
+    #[must_use]
+    pub fn with_phase_function(
+        grid: Arc<DensityGrid>,
+        bounds: AABB,
+        scale: f64,
+        phase_function: Arc<dyn Material>,
+    ) -> Self {
+        let majorant = scale * grid.max();
+        Self { grid, bounds, scale, majorant, phase_function }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn grid(&self) -> &Arc<DensityGrid> { &self.grid }
+
+    #[inline]
+    #[must_use]
+    pub const fn majorant(&self) -> f64 { self.majorant }
+
+    /// The density at world-space `p`.
+    #[must_use]
+    pub fn density(&self, p: Point3) -> f64 {
+        let b = self.bounds;
+        let local = Point3::new(
+            (p.x - b.x.min) / b.x.size(),
+            (p.y - b.y.min) / b.y.size(),
+            (p.z - b.z.min) / b.z.size(),
+        );
+        self.scale * self.grid.sample(local)
+    }
+
+    /// An unbiased estimate of the fraction of light that travels along `ray`
+    /// through the interval `t` without being scattered.
+    #[must_use]
+    pub fn transmittance(&self, rng: &mut dyn Rng, ray: &Ray, t: Interval) -> f64 {
+        let mut transmittance = 1.0;
+        self.track(rng, ray, t, |_, density| {
+            transmittance *= 1.0 - density / self.majorant;
+            false
+        });
+        transmittance
+    }
+
+    /// Steps through tentative collisions inside `t`, handing the density at
+    /// each to `collide` until it returns `true`. Returns the parameter of
+    /// that collision.
+    fn track(
+        &self,
+        rng: &mut dyn Rng,
+        ray: &Ray,
+        t: Interval,
+        mut collide: impl FnMut(&mut dyn Rng, f64) -> bool,
+    ) -> Option<f64> {
+        let span = self.bounds.clip(ray, t)?;
+        if self.majorant <= 0.0 {
+            return None;
+        }
+        // Collisions are a distance apart, but `t` is measured in units of
+        // the direction's length.
+        let rate = self.majorant * ray.direction.length();
+        let mut t = span.min;
+        loop {
+            t -= (1.0 - random(rng)).ln() / rate;
+            if t >= span.max {
+                return None;
+            }
+            if collide(rng, self.density(ray.at(t))) {
+                return Some(t);
+            }
+        }
+    }
+}
+
+impl Hittable for GridMedium {
+    fn bounding_box(&self) -> AABB { self.bounds }
+
+    fn hit(&self, ray: &Ray, t: Interval) -> Option<HitRecord> {
+        let majorant = self.majorant;
+        let t =
+            self.track(&mut rand::rng(), ray, t, |rng, density| random(rng) * majorant < density)?;
+        Some(scattering(ray.at(t), t, &self.phase_function))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng as _;
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::prelude::{Vec3, point3};
+
+    fn unit_box() -> AABB { AABB::from((Point3::ZERO, Point3::ONE)) }
+
+    #[test]
+    fn scenario_text_and_binary_formats() {
+        let text = "\
+# a 2×1×2 puff
+dims 2 1 2
+0 0.5   # z = 0
+1 2     # z = 1
+";
+        let grid = DensityGrid::parse(text.as_bytes()).unwrap();
+        assert_eq!(grid.dims(), [2, 1, 2]);
+        assert_eq!(grid.values(), [0.0, 0.5, 1.0, 2.0]);
+        shared::assert_fuzzy_eq!(grid.max(), 2.0);
+        assert_eq!(DensityGrid::parse(&grid.to_bytes()).unwrap(), grid);
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/puff.grid");
+        let puff = DensityGrid::open(path).unwrap();
+        assert_eq!(puff.dims(), [3, 3, 3]);
+        shared::assert_fuzzy_eq!(puff.sample(Point3::splat(0.5)), 1.0);
+        assert!(matches!(DensityGrid::open("missing.grid"), Err(GridError::NotFound(_))));
+    }
+
+    #[test]
+    fn scenario_parse_errors() {
+        let message =
+            |source: &str| DensityGrid::parse(source.as_bytes()).err().unwrap().to_string();
+        assert_eq!(message("0 1 2\n"), "line 1: expected 'dims nx ny nz' first");
+        assert_eq!(message("dims 2 0 1\n"), "line 1: grid 2×0×1 has no voxels");
+        assert!(message("dims 2 1 1\n0 x\n").starts_with("line 2: expected a density, found 'x'"));
+        assert_eq!(
+            message("dims 2 1 1\n0 -1\n"),
+            "line 2: density -1 is not finite and non-negative"
+        );
+        assert_eq!(message("dims 2 1 1\n0\n"), "line 2: expected 2 voxels, found 1");
+        assert_eq!(message("dims 1 1 1\n\n0 1\n"), "line 3: more than the 1 voxels declared");
+        assert_eq!(message("# nothing\n"), "line 1: missing 'dims nx ny nz'");
+        // A huge declared size is only an error, not a huge allocation.
+        assert_eq!(
+            message("dims 100000 100000 100\n0\n"),
+            "line 2: expected 1000000000000 voxels, found 1"
+        );
+
+        let mut truncated = DensityGrid::new([2, 1, 1], vec![0.0, 1.0]).to_bytes();
+        truncated.pop();
+        assert!(
+            DensityGrid::parse(&truncated).unwrap_err().to_string().contains("expected 2 voxels")
+        );
+    }
+
+    #[test]
+    fn scenario_trilinear_sampling() {
+        // Voxel centres sit at x = 0.25 and x = 0.75.
+        let grid = DensityGrid::new([2, 1, 1], vec![1.0, 3.0]);
+        shared::assert_fuzzy_eq!(grid.sample(point3(0.25, 0.5, 0.5)), 1.0);
+        shared::assert_fuzzy_eq!(grid.sample(point3(0.5, 0.5, 0.5)), 2.0);
+        shared::assert_fuzzy_eq!(grid.sample(point3(0.75, 0.1, 0.9)), 3.0);
+        // Clamped between the outermost centres and the faces.
+        shared::assert_fuzzy_eq!(grid.sample(point3(0.0, 0.5, 0.5)), 1.0);
+        shared::assert_fuzzy_eq!(grid.sample(point3(1.0, 0.5, 0.5)), 3.0);
+        shared::assert_fuzzy_eq!(grid.sample(point3(1.5, 0.5, 0.5)), 0.0);
+    }
+
+    #[test]
+    fn scenario_tracking_matches_beer_lambert() {
+        // A uniform grid behaves like a constant medium: across one unit at
+        // density 1, e^(−1) of the light gets through.
+        let grid = Arc::new(DensityGrid::new([2, 2, 2], vec![0.5; 8]));
+        let medium = GridMedium::new(grid, unit_box(), 2.0, Color3::WHITE);
+        shared::assert_fuzzy_eq!(medium.majorant(), 1.0);
+
+        let ray = Ray::new(point3(0.5, 0.5, -1.0), 2.0 * Vec3::Z, None);
+        let t = Interval::new(0.001, f64::INFINITY);
+        let trials = 20_000_u32;
+        let passed = (0..trials).filter(|_| medium.hit(&ray, t).is_none()).count();
+        let fraction = f64::from(u32::try_from(passed).unwrap()) / f64::from(trials);
+        assert!((fraction - (-1.0_f64).exp()).abs() < 0.02, "transmitted {fraction}");
+    }
+
+    #[test]
+    fn scenario_ratio_tracking_estimates_transmittance() {
+        // Density 1 along x = 0.5, under a majorant of 2.
+        let grid = Arc::new(DensityGrid::new([2, 1, 1], vec![0.0, 1.0]));
+        let medium = GridMedium::new(grid, unit_box(), 2.0, Color3::WHITE);
+        shared::assert_fuzzy_eq!(medium.density(point3(0.5, 0.5, 0.5)), 1.0);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let ray = Ray::new(point3(0.5, 0.5, -1.0), Vec3::Z, None);
+        let t = Interval::new(0.001, f64::INFINITY);
+        let estimates = core::iter::repeat_with(|| medium.transmittance(&mut rng, &ray, t));
+        let mean = estimates.take(5000).sum::<f64>() / 5000.0;
+        assert!((mean - (-1.0_f64).exp()).abs() < 0.02, "estimated {mean}");
+    }
+
+    #[test]
+    fn scenario_empty_voxels_never_scatter() {
+        // Dense on the +x side, empty on the −x side.
+        let grid = Arc::new(DensityGrid::new([4, 1, 1], vec![0.0, 0.0, 0.0, 50.0]));
+        let medium = GridMedium::new(grid, unit_box(), 1.0, Color3::WHITE);
+        let t = Interval::new(0.001, f64::INFINITY);
+
+        let ray = Ray::new(point3(-1.0, 0.5, 0.5), Vec3::X, None);
+        for _ in 0..1000 {
+            let rec = medium.hit(&ray, t).unwrap();
+            // Density only rises past the third voxel centre.
+            assert!(rec.p.x > 0.625, "scattered at {}", rec.p);
+        }
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let miss = Ray::new(point3(0.1, 0.5, -1.0), Vec3::Z, None);
+        shared::assert_fuzzy_eq!(medium.transmittance(&mut rng, &miss, t), 1.0);
+        assert!(medium.hit(&Ray::new(point3(0, 3, 0), Vec3::X, None), t).is_none());
+    }
+}
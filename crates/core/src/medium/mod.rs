@@ -0,0 +1,162 @@
+//! Participating media: volumes that scatter light throughout their
+//! interior rather than at a surface.
+//!
+//! - [`ConstantMedium`] fills a closed boundary with uniform density.
+//! - [`GridMedium`] reads its density from a voxel [`DensityGrid`].
+
+mod grid;
+
+use std::sync::Arc;
+
+use shared::random;
+
+pub use self::grid::{DensityGrid, GridError, GridMedium};
+use crate::prelude::{
+    AABB,
+    Color3,
+    HitRecord,
+    Hittable,
+    Interval,
+    Isotropic,
+    Material,
+    Point3,
+    Ray,
+    Texture,
+    Vec3,
+    interval,
+};
+
+/// A volume of uniform density filling a closed, convex `boundary`: fog,
+/// smoke, or the inside of a subsurface-looking object.
+///
+/// A ray travelling through it scatters after an exponentially distributed
+/// distance, so thin or sparse volumes let most rays through. Scattering is
+/// done by the `phase_function` material, [`Isotropic`] unless chosen
+/// otherwise.
+pub struct ConstantMedium {
+    boundary: Arc<dyn Hittable>,
+    /// −1 / density, the scale of the free-flight distance.
+    neg_inv_density: f64,
+    phase_function: Arc<dyn Material>,
+}
+
+impl ConstantMedium {
+    /// A medium that scatters with an [`Isotropic`] phase function of the
+    /// given albedo.
+    #[must_use]
+    pub fn new(boundary: Arc<dyn Hittable>, density: f64, albedo: Color3) -> Self {
+        Self::with_phase_function(boundary, density, Arc::new(Isotropic::new(albedo)))
+    }
+
+    #[must_use]
+    pub fn with_texture(
+        boundary: Arc<dyn Hittable>,
+        density: f64,
+        texture: Arc<dyn Texture>,
+    ) -> Self {
+        Self::with_phase_function(boundary, density, Arc::new(Isotropic::with_texture(texture)))
+    }
+
+    #[must_use]
+    pub fn with_phase_function(
+        boundary: Arc<dyn Hittable>,
+        density: f64,
+        phase_function: Arc<dyn Material>,
+    ) -> Self {
+        Self { boundary, neg_inv_density: -1.0 / density, phase_function }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn density(&self) -> f64 { -1.0 / self.neg_inv_density }
+}
+
+impl Hittable for ConstantMedium {
+    fn bounding_box(&self) -> AABB { self.boundary.bounding_box() }
+
+    fn hit(&self, ray: &Ray, t: Interval) -> Option<HitRecord> {
+        // Find where the ray's line enters and leaves the boundary, whatever
+        // `t` is: a ray that starts inside has its entry behind it.
+        let entry = self.boundary.hit(ray, Interval::UNIVERSE)?;
+        let exit = self.boundary.hit(ray, interval(entry.t + 0.0001, f64::INFINITY))?;
+
+        // Only the part of the span inside `t`, and never behind the origin.
+        let span = interval(entry.t.max(t.min).max(0.0), exit.t.min(t.max));
+        if span.size() <= 0.0 {
+            return None;
+        }
+
+        let length = ray.direction.length();
+        let distance_inside = span.size() * length;
+        let hit_distance = self.neg_inv_density * random(&mut rand::rng()).ln();
+        if hit_distance > distance_inside {
+            return None;
+        }
+
+        let t = span.min + hit_distance / length;
+        Some(scattering(ray.at(t), t, &self.phase_function))
+    }
+}
+
+/// The record of a ray scattering inside a medium at `p`. A scattering event
+/// has no surface, so the normal and face are arbitrary.
+fn scattering(p: Point3, t: f64, phase_function: &Arc<dyn Material>) -> HitRecord {
+    HitRecord {
+        p,
+        normal: Vec3::X,
+        t,
+        u: 0.0,
+        v: 0.0,
+        color: None,
+        material: Arc::clone(phase_function),
+        is_front_face: true,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Sphere, point3};
+
+    fn ball(density: f64) -> ConstantMedium {
+        let unused: Arc<dyn Material> = Arc::new(Isotropic::new(Color3::WHITE));
+        let boundary = Arc::new(Sphere::new(Point3::ZERO, None, 1.0, unused));
+        ConstantMedium::new(boundary, density, Color3::new(0.5, 0.5, 0.5))
+    }
+
+    const FORWARD: Interval = Interval::new(0.001, f64::INFINITY);
+
+    #[test]
+    fn scenario_dense_medium_scatters_at_the_boundary() {
+        let fog = ball(1e9);
+        let rec = fog.hit(&Ray::new(point3(0, 0, 5), -Vec3::Z, None), FORWARD).unwrap();
+        assert!((rec.t - 4.0).abs() < 1e-6, "t = {}", rec.t);
+
+        // Starting inside, the scatter happens right where the ray begins.
+        let rec = fog.hit(&Ray::new(Point3::ZERO, Vec3::Z, None), FORWARD).unwrap();
+        assert!((rec.t - 0.001).abs() < 1e-6, "t = {}", rec.t);
+
+        assert!(fog.hit(&Ray::new(point3(0, 2, 5), -Vec3::Z, None), FORWARD).is_none());
+        // The volume is entirely behind this ray.
+        assert!(fog.hit(&Ray::new(point3(0, 0, -5), -Vec3::Z, None), FORWARD).is_none());
+        // Or beyond the end of the interval.
+        assert!(fog.hit(&Ray::new(point3(0, 0, 5), -Vec3::Z, None), interval(0, 3.5)).is_none());
+    }
+
+    #[test]
+    fn scenario_transmittance_follows_beer_lambert() {
+        // Two units of medium at density 0.5: e^(−1) of the rays get through.
+        let fog = ball(0.5);
+        // A long direction vector must not change the distance travelled.
+        let ray = Ray::new(point3(0, 0, 5), -3.0 * Vec3::Z, None);
+        let trials = 20_000_u32;
+        let passed = (0..trials).filter(|_| fog.hit(&ray, FORWARD).is_none()).count();
+        let fraction = f64::from(u32::try_from(passed).unwrap()) / f64::from(trials);
+        assert!((fraction - (-1.0_f64).exp()).abs() < 0.02, "transmitted {fraction}");
+        assert!(ball(0.0).hit(&ray, FORWARD).is_none());
+    }
+}
@@ -0,0 +1,445 @@
+//! Indexed triangle meshes and the file formats they load from.
+//!
+//! A [`TriangleMesh`] keeps its vertices in shared buffers and its triangles
+//! as index triples under a private [`LinearBvh`], so a model of any size is
+//! a single object in the scene list.
+//!
+//! - [`Obj`] reads Wavefront `.obj` files and their `.mtl` material libraries.
+//! - [`read_ply`] and [`read_stl`] read Stanford PLY and STL files;
+//!   [`TriangleMesh::open`] picks one by extension.
+
+mod obj;
+mod ply;
+mod stl;
+
+use core::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fs, io};
+
+use rand::prelude::Rng;
+use shared::random;
+
+pub use self::obj::{MaterialLibrary, Obj, ObjObject, load_mtl};
+pub use self::ply::read_ply;
+pub use self::stl::read_stl;
+use crate::bvh::{LinearBvh, SplitMethod};
+use crate::image::ImageError;
+use crate::prelude::{
+    AABB,
+    Color3,
+    HitRecord,
+    Hittable,
+    Interval,
+    Material,
+    Point3,
+    Ray,
+    Vec3,
+    interval,
+};
+use crate::triangle::{Attributes, Culling, area, bounds, hit_triangle, sample_point};
+
+/// Why a mesh file could not be loaded.
+#[derive(Debug)]
+pub enum MeshError {
+    /// Nothing exists at the path.
+    NotFound(PathBuf),
+    /// The extension does not name a format this loader reads.
+    UnsupportedFormat(PathBuf),
+    /// The file could not be read, or is not valid binary data for its
+    /// format.
+    Io { path: PathBuf, source: io::Error },
+    /// The file is malformed. `line` is 1-based; `path` is empty for
+    /// in-memory sources.
+    Parse { path: PathBuf, line: usize, message: String },
+    /// A texture the file refers to could not be loaded.
+    Texture(ImageError),
+}
+
+impl MeshError {
+    fn io(path: PathBuf, source: io::Error) -> Self {
+        match source.kind() {
+            io::ErrorKind::NotFound => Self::NotFound(path),
+            _ => Self::Io { path, source },
+        }
+    }
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::NotFound(ref path) => write!(f, "mesh '{}' not found", path.display()),
+            Self::UnsupportedFormat(ref path) => {
+                write!(f, "no mesh reader for '{}'", path.display())
+            }
+            Self::Io { ref path, ref source } => {
+                write!(f, "cannot read mesh '{}': {source}", path.display())
+            }
+            Self::Parse { ref path, line, ref message } if path.as_os_str().is_empty() => {
+                write!(f, "line {line}: {message}")
+            }
+            Self::Parse { ref path, line, ref message } => {
+                write!(f, "{}:{line}: {message}", path.display())
+            }
+            Self::Texture(ref source) => source.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for MeshError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match *self {
+            Self::Io { ref source, .. } => Some(source),
+            Self::Texture(ref source) => Some(source),
+            Self::NotFound(_) | Self::UnsupportedFormat(_) | Self::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<ImageError> for MeshError {
+    fn from(source: ImageError) -> Self { Self::Texture(source) }
+}
+
+// ---------------------------------------------------------------------------
+// TriangleMesh
+// ---------------------------------------------------------------------------
+
+/// A triangle mesh with per-vertex positions and optional per-vertex normals,
+/// texture coordinates and colours, all sharing one material.
+///
+/// Triangles are counter-clockwise seen from the front, as for
+/// [`Triangle`](crate::prelude::Triangle).
+pub struct TriangleMesh {
+    positions: Vec<Point3>,
+    /// Empty, or one shading normal per position.
+    normals: Vec<Vec3>,
+    /// Empty, or one `(u, v)` per position.
+    uvs: Vec<(f64, f64)>,
+    /// Empty, or one colour per position.
+    colors: Vec<Color3>,
+    /// Vertex indices of each triangle, in BVH leaf order.
+    triangles: LinearBvh<[u32; 3]>,
+    /// Running total of the triangles' areas, in the same order, for
+    /// picking triangles by area.
+    area_cdf: Vec<f64>,
+    pub material: Arc<dyn Material>,
+    pub culling: Culling,
+}
+
+impl TriangleMesh {
+    /// Builds the mesh and its BVH.
+    ///
+    /// # Panics
+    ///
+    /// If an index is out of range for `positions`.
+    #[must_use]
+    pub fn new(
+        positions: Vec<Point3>,
+        indices: Vec<[u32; 3]>,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(
+            indices.iter().flatten().all(|&i| to_usize(i) < positions.len()),
+            "mesh index out of range"
+        );
+
+        let triangles = LinearBvh::build(indices, SplitMethod::default(), |&[a, b, c]| {
+            bounds([a, b, c].map(|i| positions.get(to_usize(i)).copied().unwrap_or_default()))
+        });
+        let area_cdf = triangles
+            .primitives()
+            .iter()
+            .scan(0.0, |total, &indices| {
+                *total += gather(&positions, indices).map_or(0.0, area);
+                Some(*total)
+            })
+            .collect();
+
+        Self {
+            positions,
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            colors: Vec::new(),
+            triangles,
+            area_cdf,
+            material,
+            culling: Culling::DoubleSided,
+        }
+    }
+
+    /// Reads a `.ply` or `.stl` file, choosing the reader from its extension.
+    /// OBJ files can hold several materials, so they load through
+    /// [`Obj::open`] instead.
+    pub fn open(path: impl AsRef<Path>, material: Arc<dyn Material>) -> Result<Self, MeshError> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+        let extension = extension.to_ascii_lowercase();
+        if !matches!(&*extension, "ply" | "stl") {
+            return Err(MeshError::UnsupportedFormat(path.to_path_buf()));
+        }
+
+        let data = fs::read(path).map_err(|source| MeshError::io(path.to_path_buf(), source))?;
+        match &*extension {
+            "ply" => ply::parse_ply(&data, path, material),
+            _ => stl::parse_stl(&data, path, material),
+        }
+    }
+
+    /// Adds per-vertex shading normals.
+    ///
+    /// # Panics
+    ///
+    /// If there is not exactly one normal per position.
+    #[must_use]
+    pub fn with_normals(self, normals: Vec<Vec3>) -> Self {
+        assert_eq!(normals.len(), self.positions.len(), "one normal per vertex");
+        Self { normals, ..self }
+    }
+
+    /// Adds per-vertex texture coordinates.
+    ///
+    /// # Panics
+    ///
+    /// If there is not exactly one `(u, v)` per position.
+    #[must_use]
+    pub fn with_uvs(self, uvs: Vec<(f64, f64)>) -> Self {
+        assert_eq!(uvs.len(), self.positions.len(), "one uv per vertex");
+        Self { uvs, ..self }
+    }
+
+    /// Adds per-vertex colours, reported as [`HitRecord::color`] so that a
+    /// [`Lambertian`](crate::prelude::Lambertian) uses them as albedo.
+    ///
+    /// # Panics
+    ///
+    /// If there is not exactly one colour per position.
+    #[must_use]
+    pub fn with_colors(self, colors: Vec<Color3>) -> Self {
+        assert_eq!(colors.len(), self.positions.len(), "one colour per vertex");
+        Self { colors, ..self }
+    }
+
+    #[must_use]
+    pub fn with_culling(self, culling: Culling) -> Self { Self { culling, ..self } }
+
+    #[inline]
+    #[must_use]
+    pub fn positions(&self) -> &[Point3] { &self.positions }
+
+    #[inline]
+    #[must_use]
+    pub fn normals(&self) -> &[Vec3] { &self.normals }
+
+    #[inline]
+    #[must_use]
+    pub fn uvs(&self) -> &[(f64, f64)] { &self.uvs }
+
+    #[inline]
+    #[must_use]
+    pub fn colors(&self) -> &[Color3] { &self.colors }
+
+    /// Vertex indices of every triangle, in no particular order.
+    #[inline]
+    #[must_use]
+    pub fn indices(&self) -> &[[u32; 3]] { self.triangles.primitives() }
+
+    #[inline]
+    #[must_use]
+    pub fn triangle_count(&self) -> usize { self.indices().len() }
+}
+
+impl Hittable for TriangleMesh {
+    fn bounding_box(&self) -> AABB { self.triangles.bounding_box() }
+
+    fn hit(&self, ray: &Ray, t: Interval) -> Option<HitRecord> {
+        self.triangles.hit_with(ray, t, |&indices, t| {
+            let vertices = gather(&self.positions, indices)?;
+            let attributes = Attributes {
+                normals: gather(&self.normals, indices),
+                uvs: gather(&self.uvs, indices),
+                colors: gather(&self.colors, indices),
+            };
+            hit_triangle(vertices, attributes, &self.material, self.culling, ray, t)
+        })
+    }
+
+    fn is_sampleable(&self) -> bool { self.total_area().is_some() }
+
+    /// Points are sampled uniformly by area over the whole mesh. A direction
+    /// may cross several triangles, any of which could have been sampled, so
+    /// the density sums distance² / (cos θ × total area) over every one.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> Option<f64> {
+        let total = self.total_area()?;
+        let ray = Ray::new(origin, direction, None);
+        let mut density = 0.0;
+        let mut t_min = 0.001;
+        while let Some(rec) = self.hit_faces(&ray, interval(t_min, f64::INFINITY)) {
+            let distance_squared = rec.t * rec.t * direction.length_squared();
+            let cosine = direction.dot(rec.normal).abs() / direction.length();
+            density += distance_squared / (cosine * total);
+            t_min = rec.t + 1e-6;
+        }
+        Some(density)
+    }
+
+    fn random(&self, rng: &mut dyn Rng, origin: Point3) -> Option<Vec3> {
+        let target = random(rng) * self.total_area()?;
+        let index = self.area_cdf.partition_point(|&area| area <= target);
+        let last = self.indices().len().checked_sub(1)?;
+        let vertices = gather(&self.positions, *self.indices().get(index.min(last))?)?;
+        Some(sample_point(rng, vertices) - origin)
+    }
+}
+
+impl TriangleMesh {
+    /// The surface area to sample lights over, or `None` if there is none.
+    fn total_area(&self) -> Option<f64> {
+        self.area_cdf.last().copied().filter(|&total| total > 0.0)
+    }
+
+    /// Like [`Hittable::hit`], but with flat normals and both sides of every
+    /// face, as light sampling sees the geometry.
+    fn hit_faces(&self, ray: &Ray, t: Interval) -> Option<HitRecord> {
+        self.triangles.hit_with(ray, t, |&indices, t| {
+            let vertices = gather(&self.positions, indices)?;
+            let attributes = Attributes::default();
+            hit_triangle(vertices, attributes, &self.material, Culling::DoubleSided, ray, t)
+        })
+    }
+}
+
+/// The three entries of `buffer` a triangle refers to, or `None` if the
+/// buffer is empty.
+fn gather<T: Copy>(buffer: &[T], [a, b, c]: [u32; 3]) -> Option<[T; 3]> {
+    let at = |i: u32| buffer.get(to_usize(i)).copied();
+    Some([at(a)?, at(b)?, at(c)?])
+}
+
+/// Splits a polygon into a fan of triangles around its first vertex.
+fn fan(polygon: &[u32]) -> impl Iterator<Item = [u32; 3]> {
+    let (first, rest) = polygon.split_first().map_or((0, &[][..]), |(&first, rest)| (first, rest));
+    rest.windows(2).filter_map(move |pair| match *pair {
+        [b, c] => Some([first, b, c]),
+        _ => None,
+    })
+}
+
+fn to_usize(i: u32) -> usize { usize::try_from(i).unwrap_or(usize::MAX) }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng as _;
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::prelude::{Hittables, Lambertian, Triangle, color, point3};
+
+    const ANY: Interval = Interval::new(0.001, f64::INFINITY);
+
+    fn material() -> Arc<dyn Material> { Arc::new(Lambertian::new(color(0.5, 0.5, 0.5))) }
+
+    /// The `[-1, 1]³` cube as 8 shared corners and 12 outward-facing
+    /// triangles.
+    fn cube() -> (Vec<Point3>, Vec<[u32; 3]>) {
+        let corners = (0..8)
+            .map(|i: u32| {
+                let bit = |b: u32| if i & b == 0 { -1.0 } else { 1.0 };
+                point3(bit(1), bit(2), bit(4))
+            })
+            .collect();
+        let faces = vec![
+            // z = −1, then z = +1
+            [0, 2, 3],
+            [0, 3, 1],
+            [4, 5, 7],
+            [4, 7, 6],
+            // y = −1, then y = +1
+            [0, 1, 5],
+            [0, 5, 4],
+            [2, 6, 7],
+            [2, 7, 3],
+            // x = −1, then x = +1
+            [0, 4, 6],
+            [0, 6, 2],
+            [1, 3, 7],
+            [1, 7, 5],
+        ];
+        (corners, faces)
+    }
+
+    #[test]
+    fn scenario_mesh_matches_separate_triangles() {
+        let (positions, faces) = cube();
+        let separate: Hittables = faces
+            .iter()
+            .map(|&[a, b, c]| -> Arc<dyn Hittable> {
+                let at = |i: u32| positions.get(to_usize(i)).copied().unwrap();
+                Arc::new(Triangle::new(at(a), at(b), at(c), material()))
+            })
+            .collect();
+        let mesh = TriangleMesh::new(positions, faces, material());
+        assert_eq!(mesh.triangle_count(), 12);
+
+        let mut rng = StdRng::seed_from_u64(9);
+        for _ in 0..2000 {
+            let origin = Vec3::random_range(&mut rng, -4.0, 4.0);
+            let ray = Ray::new(origin, Vec3::random_unit(&mut rng), None);
+            let expected = separate.hit(&ray, ANY).map(|rec| (rec.t, rec.is_front_face));
+            let actual = mesh.hit(&ray, ANY).map(|rec| (rec.t, rec.is_front_face));
+            assert_eq!(expected, actual, "ray from {origin}");
+        }
+    }
+
+    #[test]
+    fn scenario_faces_point_outwards() {
+        let (positions, faces) = cube();
+        let mesh = TriangleMesh::new(positions, faces, material()).with_culling(Culling::BackFace);
+        for axis in Vec3::AXES {
+            for dir in [axis, -axis] {
+                let ray = Ray::new(-5.0 * dir + 0.3 * dir.cross(Vec3::ONE), dir, None);
+                let rec = mesh.hit(&ray, ANY).unwrap();
+                shared::assert_fuzzy_eq!(rec.t, 4.0);
+                assert_eq!(rec.normal, -dir);
+                // The far side is culled from inside.
+                assert!(mesh.hit(&ray, interval(rec.t + 0.001, f64::INFINITY)).is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn scenario_vertex_buffers_are_interpolated() {
+        let positions = vec![Point3::ZERO, Point3::X, Point3::Y, point3(1.0, 1.0, 0.0)];
+        let normals = vec![Vec3::Z, Vec3::Z, Vec3::Z, Vec3::X];
+        let uvs = vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)];
+        let mesh = TriangleMesh::new(positions, vec![[0, 1, 2], [1, 3, 2]], material())
+            .with_normals(normals)
+            .with_uvs(uvs);
+
+        let rec = mesh.hit(&Ray::new(point3(0.75, 0.75, 1.0), Vec3::NEG_Z, None), ANY).unwrap();
+        shared::assert_fuzzy_eq!(rec.u, 0.75);
+        shared::assert_fuzzy_eq!(rec.v, 0.75);
+        assert!(rec.normal.x > 0.0);
+
+        let bbox = mesh.bounding_box();
+        assert_eq!((bbox.x.max, bbox.y.max), (1.0, 1.0));
+        assert!(bbox.z.size() > 0.0);
+    }
+
+    #[test]
+    fn scenario_open_checks_extension_then_file() {
+        let unsupported = TriangleMesh::open("bunny.obj", material());
+        assert!(matches!(unsupported, Err(MeshError::UnsupportedFormat(_))));
+        let missing = TriangleMesh::open("/nonexistent/bunny.ply", material());
+        assert!(matches!(missing, Err(MeshError::NotFound(_))));
+    }
+
+    #[test]
+    #[should_panic = "mesh index out of range"]
+    fn scenario_rejects_bad_indices() {
+        let _mesh = TriangleMesh::new(vec![Point3::ZERO; 3], vec![[0, 1, 3]], material());
+    }
+}
@@ -0,0 +1,662 @@
+//! Wavefront OBJ geometry and MTL material libraries.
+//!
+//! Only polygonal geometry is read: `v`, `vt`, `vn` and `f`, with `g`/`o`
+//! and `usemtl` splitting faces into objects. Lines, points, curves and
+//! smoothing groups are skipped.
+
+use core::str::SplitWhitespace;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::mesh::{MeshError, TriangleMesh, fan};
+use crate::prelude::{
+    Color3,
+    Dielectric,
+    DiffuseLight,
+    Hittable,
+    Hittables,
+    ImageTexture,
+    Lambertian,
+    Material,
+    Metal,
+    Point3,
+    Vec3,
+    vec3,
+};
+use crate::texture::{Filter, Wrap};
+
+/// Materials by name, as defined by `newmtl` statements.
+pub type MaterialLibrary = HashMap<String, Arc<dyn Material>>;
+
+/// The faces of one group that share a material.
+pub struct ObjObject {
+    /// Name from the last `g` or `o` statement, or `"default"`.
+    pub group: String,
+    /// Name from the last `usemtl` statement.
+    pub material: Option<String>,
+    pub mesh: TriangleMesh,
+}
+
+/// A parsed OBJ file: one [`ObjObject`] per group and material, in order of
+/// first use.
+pub struct Obj {
+    pub objects: Vec<ObjObject>,
+}
+
+impl Obj {
+    /// Reads `path` and the MTL libraries it names, which are looked up
+    /// relative to `path`'s directory.
+    ///
+    /// Faces with no `usemtl`, or naming a material no library defines, use
+    /// `fallback`.
+    pub fn open(path: impl AsRef<Path>, fallback: &Arc<dyn Material>) -> Result<Self, MeshError> {
+        let path = path.as_ref();
+        let source = read(path)?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        parse(&source, path, MaterialLibrary::new(), fallback, &mut |name| load_mtl(dir.join(name)))
+    }
+
+    /// Parses OBJ text, taking materials from `library` and ignoring
+    /// `mtllib` statements.
+    pub fn parse(
+        source: &str,
+        library: &MaterialLibrary,
+        fallback: &Arc<dyn Material>,
+    ) -> Result<Self, MeshError> {
+        parse(source, Path::new(""), library.clone(), fallback, &mut |_| Ok(MaterialLibrary::new()))
+    }
+
+    #[must_use]
+    pub fn triangle_count(&self) -> usize {
+        self.objects.iter().map(|object| object.mesh.triangle_count()).sum()
+    }
+}
+
+impl From<Obj> for Hittables {
+    fn from(obj: Obj) -> Self {
+        obj.objects
+            .into_iter()
+            .map(|object| -> Arc<dyn Hittable> { Arc::new(object.mesh) })
+            .collect()
+    }
+}
+
+fn read(path: &Path) -> Result<String, MeshError> {
+    fs::read_to_string(path).map_err(|source| MeshError::io(path.to_path_buf(), source))
+}
+
+/// Strips a trailing `#` comment.
+fn content(line: &str) -> &str { line.split('#').next().unwrap_or_default() }
+
+// ---------------------------------------------------------------------------
+// OBJ
+// ---------------------------------------------------------------------------
+
+/// Resolved, zero-based position, texture-coordinate and normal indices of
+/// one face corner.
+type Corner = (usize, Option<usize>, Option<usize>);
+
+fn parse(
+    source: &str,
+    path: &Path,
+    mut library: MaterialLibrary,
+    fallback: &Arc<dyn Material>,
+    mtllib: &mut dyn FnMut(&str) -> Result<MaterialLibrary, MeshError>,
+) -> Result<Obj, MeshError> {
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut normals = Vec::new();
+
+    let mut group = String::from("default");
+    let mut material = None;
+    let mut parts: Vec<Part> = Vec::new();
+    let mut part_of: HashMap<(String, Option<String>), usize> = HashMap::new();
+
+    for (number, line) in (1..).zip(source.lines()) {
+        let error =
+            |message: String| MeshError::Parse { path: path.to_path_buf(), line: number, message };
+
+        let mut tokens = content(line).split_whitespace();
+        let Some(keyword) = tokens.next() else { continue };
+        match keyword {
+            "v" => positions.push(vector(&mut tokens).map_err(error)?),
+            "vn" => normals.push(vector(&mut tokens).map_err(error)?),
+            "vt" => {
+                let u = number_of(tokens.next()).map_err(error)?;
+                let v = tokens.next().map_or(Ok(0.0), |t| number_of(Some(t))).map_err(error)?;
+                uvs.push((u, v));
+            }
+            "f" => {
+                let corners = tokens
+                    .map(|token| corner(token, positions.len(), uvs.len(), normals.len()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                if corners.len() < 3 {
+                    let message = format!("face needs 3 or more vertices, found {}", corners.len());
+                    return Err(error(message));
+                }
+
+                let key = (group.clone(), material.clone());
+                let index = *part_of.entry(key).or_insert_with(|| {
+                    parts.push(Part::new(group.clone(), material.clone()));
+                    parts.len() - 1
+                });
+                if let Some(part) = parts.get_mut(index) {
+                    part.add_face(&corners, &positions, &uvs, &normals);
+                }
+            }
+            "g" | "o" => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                group = if name.is_empty() { String::from("default") } else { name };
+            }
+            "usemtl" => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                if name.is_empty() {
+                    return Err(error(String::from("usemtl needs a material name")));
+                }
+                material = Some(name);
+            }
+            "mtllib" => {
+                for name in tokens {
+                    library.extend(mtllib(name)?);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let objects = parts
+        .into_iter()
+        .map(|part| {
+            let material = part
+                .material
+                .as_ref()
+                .and_then(|name| library.get(name))
+                .map_or_else(|| Arc::clone(fallback), Arc::clone);
+            part.build(material)
+        })
+        .collect();
+    Ok(Obj { objects })
+}
+
+/// Parses a face corner: `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+fn corner(token: &str, positions: usize, uvs: usize, normals: usize) -> Result<Corner, String> {
+    let mut fields = token.split('/');
+    let position = resolve(fields.next().unwrap_or_default(), positions, "vertex")?;
+    let mut optional = |count, what| {
+        fields
+            .next()
+            .filter(|field| !field.is_empty())
+            .map(|field| resolve(field, count, what))
+            .transpose()
+    };
+    let uv = optional(uvs, "texture coordinate")?;
+    let normal = optional(normals, "normal")?;
+    if fields.next().is_some() {
+        return Err(format!("malformed face vertex '{token}'"));
+    }
+    Ok((position, uv, normal))
+}
+
+/// Turns a one-based (or negative, counting back from the last) index into a
+/// zero-based one.
+fn resolve(field: &str, count: usize, what: &str) -> Result<usize, String> {
+    let index: i64 = field.parse().map_err(|e| format!("bad {what} index '{field}': {e}"))?;
+    let magnitude = usize::try_from(index.unsigned_abs()).unwrap_or(usize::MAX);
+    let resolved = if index > 0 { Some(magnitude - 1) } else { count.checked_sub(magnitude) };
+    resolved
+        .filter(|&i| i < count && index != 0)
+        .ok_or_else(|| format!("{what} index {index} out of range ({count} defined)"))
+}
+
+fn number_of(token: Option<&str>) -> Result<f64, String> {
+    let token = token.ok_or_else(|| String::from("expected a number"))?;
+    token.parse().map_err(|e| format!("expected a number, found '{token}': {e}"))
+}
+
+fn vector(tokens: &mut SplitWhitespace<'_>) -> Result<Vec3, String> {
+    Ok(vec3(number_of(tokens.next())?, number_of(tokens.next())?, number_of(tokens.next())?))
+}
+
+/// Faces of one group and material, with vertices re-indexed so each
+/// distinct corner is stored once.
+struct Part {
+    group: String,
+    material: Option<String>,
+    vertex_of: HashMap<Corner, u32>,
+    positions: Vec<Point3>,
+    uvs: Vec<(f64, f64)>,
+    normals: Vec<Vec3>,
+    indices: Vec<[u32; 3]>,
+    /// Whether every corner so far had a texture coordinate.
+    all_uvs: bool,
+    /// Whether every corner so far had a normal.
+    all_normals: bool,
+}
+
+impl Part {
+    fn new(group: String, material: Option<String>) -> Self {
+        Self {
+            group,
+            material,
+            vertex_of: HashMap::new(),
+            positions: Vec::new(),
+            uvs: Vec::new(),
+            normals: Vec::new(),
+            indices: Vec::new(),
+            all_uvs: true,
+            all_normals: true,
+        }
+    }
+
+    /// Adds a polygon as a fan of triangles around its first corner.
+    fn add_face(
+        &mut self,
+        corners: &[Corner],
+        positions: &[Point3],
+        uvs: &[(f64, f64)],
+        normals: &[Vec3],
+    ) {
+        let ids: Vec<u32> =
+            corners.iter().map(|&c| self.vertex(c, positions, uvs, normals)).collect();
+        self.indices.extend(fan(&ids));
+    }
+
+    fn vertex(
+        &mut self,
+        corner: Corner,
+        positions: &[Point3],
+        uvs: &[(f64, f64)],
+        normals: &[Vec3],
+    ) -> u32 {
+        if let Some(&id) = self.vertex_of.get(&corner) {
+            return id;
+        }
+        let id = u32::try_from(self.positions.len()).unwrap_or(u32::MAX);
+        self.vertex_of.insert(corner, id);
+
+        let (position, uv, normal) = corner;
+        self.positions.push(positions.get(position).copied().unwrap_or_default());
+        let uv = uv.and_then(|i| uvs.get(i).copied());
+        self.all_uvs &= uv.is_some();
+        self.uvs.push(uv.unwrap_or_default());
+        let normal = normal.and_then(|i| normals.get(i).copied());
+        self.all_normals &= normal.is_some();
+        self.normals.push(normal.unwrap_or_default());
+        id
+    }
+
+    /// Vertex attributes are only kept if every corner had them.
+    fn build(self, material: Arc<dyn Material>) -> ObjObject {
+        let mut mesh = TriangleMesh::new(self.positions, self.indices, material);
+        if self.all_uvs {
+            mesh = mesh.with_uvs(self.uvs);
+        }
+        if self.all_normals {
+            mesh = mesh.with_normals(self.normals);
+        }
+        ObjObject { group: self.group, material: self.material, mesh }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MTL
+// ---------------------------------------------------------------------------
+
+/// Reads an MTL library. `map_Kd` paths are relative to its directory.
+///
+/// Each material becomes the closest engine material:
+///
+/// - a non-black `Ke` makes a [`DiffuseLight`];
+/// - `d` below 1 (or `Tr` above 0), or an `illum` model with refraction, makes
+///   a [`Dielectric`] with index `Ni` (1.5 if unset);
+/// - `Ks` brighter than `Kd`, with no `map_Kd`, makes a [`Metal`] whose fuzz
+///   falls as the Phong exponent `Ns` rises;
+/// - anything else is [`Lambertian`], textured by `map_Kd` if present.
+pub fn load_mtl(path: impl AsRef<Path>) -> Result<MaterialLibrary, MeshError> {
+    let path = path.as_ref();
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_mtl(&read(path)?, path)?
+        .into_iter()
+        .map(|(name, mtl)| Ok((name, mtl.material(dir)?)))
+        .collect()
+}
+
+/// The engine material an MTL entry maps to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Light,
+    Glass,
+    Metal,
+    Diffuse,
+}
+
+/// The MTL statements the loader understands.
+#[derive(Clone, Debug, PartialEq)]
+struct Mtl {
+    kd: Color3,
+    ks: Color3,
+    ke: Color3,
+    ns: f64,
+    ni: Option<f64>,
+    dissolve: f64,
+    illum: Option<u32>,
+    map_kd: Option<String>,
+}
+
+impl Default for Mtl {
+    fn default() -> Self {
+        Self {
+            kd: Color3::new(0.8, 0.8, 0.8),
+            ks: Color3::BLACK,
+            ke: Color3::BLACK,
+            ns: 0.0,
+            ni: None,
+            dissolve: 1.0,
+            illum: None,
+            map_kd: None,
+        }
+    }
+}
+
+impl Mtl {
+    fn kind(&self) -> Kind {
+        if self.ke.luminance() > 0.0 {
+            Kind::Light
+        } else if self.dissolve < 1.0 || matches!(self.illum, Some(4 | 6 | 7 | 9)) {
+            Kind::Glass
+        } else if self.map_kd.is_none() && self.ks.luminance() > self.kd.luminance() {
+            Kind::Metal
+        } else {
+            Kind::Diffuse
+        }
+    }
+
+    /// Roughness from the Phong exponent, `√(2 / (Ns + 2))`: 1 for `Ns = 0`,
+    /// near 0 for a tight highlight.
+    fn fuzz(&self) -> f64 { (2.0 / (self.ns.max(0.0) + 2.0)).sqrt() }
+
+    fn material(&self, dir: &Path) -> Result<Arc<dyn Material>, MeshError> {
+        Ok(match self.kind() {
+            Kind::Light => Arc::new(DiffuseLight::new(self.ke)),
+            Kind::Glass => Arc::new(Dielectric::new(self.ni.unwrap_or(1.5))),
+            Kind::Metal => Arc::new(Metal::new(self.ks, self.fuzz())),
+            Kind::Diffuse => match self.map_kd {
+                Some(ref file) => {
+                    let texture = ImageTexture::open(dir.join(file))?
+                        .with_filter(Filter::Bilinear)
+                        .with_wrap(Wrap::Repeat);
+                    Arc::new(Lambertian::with_texture(Arc::new(texture)))
+                }
+                None => Arc::new(Lambertian::new(self.kd)),
+            },
+        })
+    }
+}
+
+fn parse_mtl(source: &str, path: &Path) -> Result<Vec<(String, Mtl)>, MeshError> {
+    let mut materials: Vec<(String, Mtl)> = Vec::new();
+
+    for (number, line) in (1..).zip(source.lines()) {
+        let error =
+            |message: String| MeshError::Parse { path: path.to_path_buf(), line: number, message };
+
+        let mut tokens = content(line).split_whitespace();
+        let Some(keyword) = tokens.next() else { continue };
+        if keyword == "newmtl" {
+            let name = tokens.collect::<Vec<_>>().join(" ");
+            if name.is_empty() {
+                return Err(error(String::from("newmtl needs a material name")));
+            }
+            materials.push((name, Mtl::default()));
+            continue;
+        }
+        let Some(last) = materials.last_mut() else {
+            return Err(error(format!("'{keyword}' before any newmtl")));
+        };
+        let mtl = &mut last.1;
+
+        match keyword {
+            "Kd" => mtl.kd = rgb(&mut tokens).map_err(error)?,
+            "Ks" => mtl.ks = rgb(&mut tokens).map_err(error)?,
+            "Ke" => mtl.ke = rgb(&mut tokens).map_err(error)?,
+            "Ns" => mtl.ns = number_of(tokens.next()).map_err(error)?,
+            "Ni" => mtl.ni = Some(number_of(tokens.next()).map_err(error)?),
+            "d" => {
+                let value = tokens.find(|&t| t != "-halo");
+                mtl.dissolve = number_of(value).map_err(error)?;
+            }
+            "Tr" => mtl.dissolve = 1.0 - number_of(tokens.next()).map_err(error)?,
+            "illum" => {
+                let token = tokens.next().unwrap_or_default();
+                let model = token.parse().map_err(|e| format!("bad illum '{token}': {e}"));
+                mtl.illum = Some(model.map_err(error)?);
+            }
+            "map_Kd" => {
+                // Options such as `-s` or `-o` come first; the file name is last.
+                let file =
+                    tokens.last().ok_or_else(|| error(String::from("map_Kd needs a file")))?;
+                mtl.map_kd = Some(file.to_owned());
+            }
+            _ => {}
+        }
+    }
+    Ok(materials)
+}
+
+/// `r g b`, or a single value for grey.
+fn rgb(tokens: &mut SplitWhitespace<'_>) -> Result<Color3, String> {
+    let first = tokens.next();
+    if let Some(kind @ ("spectral" | "xyz")) = first {
+        return Err(format!("unsupported '{kind}' colour"));
+    }
+    let r = number_of(first)?;
+    match tokens.next() {
+        Some(g) => Ok(Color3::new(r, number_of(Some(g))?, number_of(tokens.next())?)),
+        None => Ok(Color3::new(r, r, r)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng as _;
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::prelude::{Interval, Ray, color, point3};
+
+    const ANY: Interval = Interval::new(0.001, f64::INFINITY);
+
+    fn grey() -> Arc<dyn Material> { Arc::new(Lambertian::new(color(0.5, 0.5, 0.5))) }
+
+    fn parse_str(source: &str) -> Result<Obj, MeshError> {
+        Obj::parse(source, &MaterialLibrary::new(), &grey())
+    }
+
+    #[test]
+    fn scenario_polygons_are_triangulated_with_shared_vertices() {
+        let source = "\
+# a unit square and a pentagon
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1 4/4/1
+v 2 0 0
+v 3 0 0
+v 3.5 1 0
+v 2.5 2 0
+v 1.5 1 0
+f -5 -4 -3 -2 -1
+";
+        let obj = parse_str(source).unwrap();
+        assert_eq!(obj.objects.len(), 1);
+        let object = obj.objects.first().unwrap();
+        assert_eq!(object.group, "default");
+        assert_eq!(object.material, None);
+        assert_eq!(object.mesh.triangle_count(), 5);
+        assert_eq!(object.mesh.positions().len(), 9);
+        // The pentagon's corners had no uvs or normals, so the mesh drops them.
+        assert!(object.mesh.uvs().is_empty() && object.mesh.normals().is_empty());
+
+        let ray = Ray::new(point3(2.5, 1.0, 1.0), Vec3::NEG_Z, None);
+        assert!(object.mesh.hit(&ray, ANY).is_some());
+    }
+
+    #[test]
+    fn scenario_attributes_survive_when_every_corner_has_them() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0.25 0.5\nvn 0 0 1\nf 1/1/1 2/1/1 3/1/1\n";
+        let obj = parse_str(source).unwrap();
+        let mesh = &obj.objects.first().unwrap().mesh;
+        assert_eq!(mesh.uvs(), &[(0.25, 0.5); 3]);
+        assert_eq!(mesh.normals(), &[Vec3::Z; 3]);
+
+        let rec = mesh.hit(&Ray::new(point3(0.2, 0.2, 1.0), Vec3::NEG_Z, None), ANY).unwrap();
+        shared::assert_fuzzy_eq!(rec.u, 0.25);
+        shared::assert_fuzzy_eq!(rec.v, 0.5);
+    }
+
+    #[test]
+    fn scenario_groups_and_materials_split_objects() {
+        let source = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+g left
+usemtl red
+f 1 2 3
+usemtl blue
+f 1 2 3
+g right
+f 1 2 3
+g left
+usemtl red
+f 3 2 1
+";
+        let red: Arc<dyn Material> = Arc::new(Lambertian::new(Color3::RED));
+        let library = MaterialLibrary::from([(String::from("red"), red)]);
+        let obj = Obj::parse(source, &library, &grey()).unwrap();
+
+        let summary: Vec<_> = obj
+            .objects
+            .iter()
+            .map(|o| (o.group.as_str(), o.material.as_deref(), o.mesh.triangle_count()))
+            .collect();
+        assert_eq!(summary, [
+            ("left", Some("red"), 2),
+            ("left", Some("blue"), 1),
+            ("right", Some("blue"), 1)
+        ]);
+        assert_eq!(obj.triangle_count(), 4);
+        assert_eq!(Hittables::from(obj).len(), 3);
+    }
+
+    #[test]
+    fn scenario_errors_carry_line_numbers() {
+        let cases = [
+            ("v 1 2\n", "line 1: expected a number"),
+            ("v 0 0 0\nv 1 0 0\n\nf 1 2 9\n", "line 4: vertex index 9 out of range (2 defined)"),
+            ("v 0 0 0\nf 1 1\n", "line 2: face needs 3 or more vertices, found 2"),
+            ("v 0 0 0\nf 1 1 x\n", "line 2: bad vertex index 'x'"),
+            ("v 0 0 0\nf 1 0 1\n", "line 2: vertex index 0 out of range"),
+            ("v 0 0 0\nf 1/1 1 1\n", "line 2: texture coordinate index 1 out of range (0 defined)"),
+        ];
+        for (source, expected) in cases {
+            let message = parse_str(source).err().unwrap().to_string();
+            assert!(message.starts_with(expected), "{message:?} does not start with {expected:?}");
+        }
+    }
+
+    #[test]
+    fn scenario_mtl_statements_choose_material_kinds() {
+        let source = "\
+# comment
+newmtl lamp
+Ke 4 4 4
+newmtl glass
+Kd 1 1 1
+d 0.5
+Ni 1.33
+newmtl chrome
+Kd 0.1 0.1 0.1
+Ks 0.9
+Ns 998
+newmtl clay
+Kd 0.7 0.4 0.3
+Ks 0.2 0.2 0.2
+newmtl textured
+Ks 1 1 1
+map_Kd -s 2 2 1 wood.png
+newmtl window
+illum 7
+";
+        let library = parse_mtl(source, Path::new("")).unwrap();
+        let kinds: Vec<_> =
+            library.iter().map(|entry| (entry.0.as_str(), entry.1.kind())).collect();
+        assert_eq!(kinds, [
+            ("lamp", Kind::Light),
+            ("glass", Kind::Glass),
+            ("chrome", Kind::Metal),
+            ("clay", Kind::Diffuse),
+            ("textured", Kind::Diffuse),
+            ("window", Kind::Glass),
+        ]);
+
+        let mtl = |name: &str| {
+            library.iter().find(|entry| entry.0 == name).map(|entry| entry.1.clone()).unwrap()
+        };
+        assert_eq!(mtl("glass").ni, Some(1.33));
+        assert_eq!(mtl("chrome").ks, color(0.9, 0.9, 0.9));
+        assert!(mtl("chrome").fuzz() < 0.05);
+        assert_eq!(mtl("clay").kd, color(0.7, 0.4, 0.3));
+        assert_eq!(mtl("textured").map_kd.as_deref(), Some("wood.png"));
+    }
+
+    #[test]
+    fn scenario_mtl_errors_carry_line_numbers() {
+        let cases = [
+            ("Kd 1 1 1\n", "line 1: 'Kd' before any newmtl"),
+            ("newmtl a\nKd 1 x 1\n", "line 2: expected a number, found 'x'"),
+            ("newmtl a\n\nKd spectral ident.spd\n", "line 3: unsupported 'spectral' colour"),
+        ];
+        for (source, expected) in cases {
+            let message = parse_mtl(source, Path::new("")).err().unwrap().to_string();
+            assert!(message.starts_with(expected), "{message:?} does not start with {expected:?}");
+        }
+    }
+
+    #[test]
+    fn scenario_open_resolves_mtllib_and_textures() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/quad.obj");
+        let obj = Obj::open(path, &grey()).unwrap();
+        assert_eq!(obj.objects.len(), 2);
+
+        // The textured quad picks up palette.png through map_Kd.
+        let textured =
+            obj.objects.iter().find(|o| o.material.as_deref() == Some("palette")).unwrap();
+        let ray = Ray::new(point3(0.5, 0.5, 1.0), Vec3::NEG_Z, None);
+        let rec = textured.mesh.hit(&ray, ANY).unwrap();
+        assert!(rec.material.scatter(&mut StdRng::seed_from_u64(1), &ray, &rec).is_some());
+
+        // The lamp's Ke makes it emissive.
+        let lamp = obj.objects.iter().find(|o| o.material.as_deref() == Some("lamp")).unwrap();
+        let ray = Ray::new(point3(0.5, 0.5, 3.0), Vec3::NEG_Z, None);
+        let rec = lamp.mesh.hit(&ray, ANY).unwrap();
+        assert_eq!(rec.material.emitted(rec.u, rec.v, rec.p), color(5.0, 5.0, 5.0));
+
+        let missing =
+            Obj::open(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/missing.obj"), &grey());
+        assert!(matches!(missing, Err(MeshError::NotFound(_))));
+    }
+}
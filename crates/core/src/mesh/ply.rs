@@ -0,0 +1,628 @@
+//! Stanford PLY meshes, ASCII or binary in either byte order.
+//!
+//! Reads the `vertex` element's positions, normals (`nx ny nz`), texture
+//! coordinates (`u v`, `s t` or `texture_u texture_v`) and colours
+//! (`red green blue`), and the `face` element's `vertex_indices` lists.
+//! Other properties and elements are skipped.
+
+use core::iter::Zip;
+use core::ops::RangeFrom;
+use core::str::{Lines, SplitWhitespace};
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::mesh::{MeshError, TriangleMesh, fan};
+use crate::prelude::{Color3, Material, Point3, TransferFunction, Vec3, vec3};
+
+/// Reads a PLY file from memory.
+///
+/// Integer colours are treated as sRGB and decoded to linear; floating-point
+/// colours are taken as linear already.
+pub fn read_ply(data: &[u8], material: Arc<dyn Material>) -> Result<TriangleMesh, MeshError> {
+    parse_ply(data, Path::new(""), material)
+}
+
+pub(crate) fn parse_ply(
+    data: &[u8],
+    path: &Path,
+    material: Arc<dyn Material>,
+) -> Result<TriangleMesh, MeshError> {
+    let (header, body_start) = parse_header(data, path)?;
+    let body = data.get(body_start..).unwrap_or_default();
+    let mut reader = match header.format {
+        Format::Ascii => {
+            let text = core::str::from_utf8(body).map_err(|e| MeshError::Parse {
+                path: path.to_path_buf(),
+                line: header.lines + 1,
+                message: e.to_string(),
+            })?;
+            let mut lines = (header.lines + 1..).zip(text.lines());
+            let (line, current) = lines.next().map_or((header.lines + 1, ""), |(n, l)| (n, l));
+            Reader::Ascii { lines, current: current.split_whitespace(), line }
+        }
+        Format::Binary { big_endian } => Reader::Binary { data: body, pos: 0, big_endian },
+    };
+
+    let vertex_count = header.elements.iter().find(|e| e.name == "vertex").map_or(0, |e| e.count);
+    let mut vertices = Vertices::default();
+    let mut faces = Vec::new();
+    for element in &header.elements {
+        match &*element.name {
+            "vertex" => vertices = read_vertices(&mut reader, element, path)?,
+            "face" => faces = read_faces(&mut reader, element, vertex_count, path)?,
+            _ => {
+                for _ in 0..element.count {
+                    for property in &element.properties {
+                        reader.list_or_scalar(property, path)?;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut mesh = TriangleMesh::new(vertices.positions, faces, material);
+    if let Some(normals) = vertices.normals {
+        mesh = mesh.with_normals(normals);
+    }
+    if let Some(uvs) = vertices.uvs {
+        mesh = mesh.with_uvs(uvs);
+    }
+    if let Some(colors) = vertices.colors {
+        mesh = mesh.with_colors(colors);
+    }
+    Ok(mesh)
+}
+
+// ---------------------------------------------------------------------------
+// Header
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Ascii,
+    Binary { big_endian: bool },
+}
+
+/// The scalar types a property can have.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => Self::I8,
+            "uchar" | "uint8" => Self::U8,
+            "short" | "int16" => Self::I16,
+            "ushort" | "uint16" => Self::U16,
+            "int" | "int32" => Self::I32,
+            "uint" | "uint32" => Self::U32,
+            "float" | "float32" => Self::F32,
+            "double" | "float64" => Self::F64,
+            _ => return None,
+        })
+    }
+
+    const fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// The value that means full intensity for a colour of this type.
+    const fn full_scale(self) -> Option<f64> {
+        match self {
+            Self::I8 => Some(127.0),
+            Self::U8 => Some(255.0),
+            Self::I16 => Some(32_767.0),
+            Self::U16 => Some(65_535.0),
+            Self::I32 => Some(2_147_483_647.0),
+            Self::U32 => Some(4_294_967_295.0),
+            Self::F32 | Self::F64 => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Property {
+    Scalar { name: String, ty: Scalar },
+    List { name: String, count: Scalar, item: Scalar },
+}
+
+impl Property {
+    fn name(&self) -> &str {
+        match *self {
+            Self::Scalar { ref name, .. } | Self::List { ref name, .. } => name,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+    /// Header line that declared the element.
+    line: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Header {
+    format: Format,
+    elements: Vec<Element>,
+    /// Number of lines up to and including `end_header`.
+    lines: usize,
+}
+
+/// Parses the header, returning it and the offset of the first body byte.
+fn parse_header(data: &[u8], path: &Path) -> Result<(Header, usize), MeshError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut pos = 0;
+    let mut lines = 0;
+
+    for (line, bytes) in (1..).zip(data.split_inclusive(|&b| b == b'\n')) {
+        let error = |message: String| MeshError::Parse { path: path.to_path_buf(), line, message };
+        let Some(bytes) = bytes.strip_suffix(b"\n") else { break };
+        pos += bytes.len() + 1;
+        lines = line;
+        let text = String::from_utf8_lossy(bytes);
+        let mut tokens = text.split_whitespace();
+
+        match (line, tokens.next()) {
+            (1, Some("ply")) => {}
+            (1, _) => return Err(error(String::from("not a PLY file"))),
+            (_, Some("format")) => {
+                format = Some(match (tokens.next(), tokens.next()) {
+                    (Some("ascii"), Some("1.0")) => Format::Ascii,
+                    (Some("binary_little_endian"), Some("1.0")) => {
+                        Format::Binary { big_endian: false }
+                    }
+                    (Some("binary_big_endian"), Some("1.0")) => Format::Binary { big_endian: true },
+                    _ => return Err(error(format!("unsupported format '{text}'"))),
+                });
+            }
+            (_, Some("element")) => {
+                let name = tokens.next().unwrap_or_default().to_owned();
+                let count = tokens.next().and_then(|c| c.parse().ok());
+                let Some(count) = count.filter(|_| !name.is_empty()) else {
+                    return Err(error(format!("malformed element '{text}'")));
+                };
+                elements.push(Element { name, count, properties: Vec::new(), line });
+            }
+            (_, Some("property")) => {
+                let Some(element) = elements.last_mut() else {
+                    return Err(error(String::from("property before any element")));
+                };
+                let ty = |name: Option<&str>| {
+                    let name = name.unwrap_or_default();
+                    Scalar::parse(name)
+                        .ok_or_else(|| error(format!("unknown property type '{name}'")))
+                };
+                let property = match tokens.next() {
+                    Some("list") => {
+                        let count = ty(tokens.next())?;
+                        let item = ty(tokens.next())?;
+                        Property::List {
+                            name: tokens.next().unwrap_or_default().to_owned(),
+                            count,
+                            item,
+                        }
+                    }
+                    name => Property::Scalar {
+                        ty: ty(name)?,
+                        name: tokens.next().unwrap_or_default().to_owned(),
+                    },
+                };
+                element.properties.push(property);
+            }
+            (_, Some("end_header")) => {
+                let format =
+                    format.ok_or_else(|| error(String::from("header has no format line")))?;
+                return Ok((Header { format, elements, lines: line }, pos));
+            }
+            (_, Some("comment" | "obj_info") | None) => {}
+            (_, Some(keyword)) => return Err(error(format!("unexpected '{keyword}' in header"))),
+        }
+    }
+    Err(MeshError::Parse {
+        path: path.to_path_buf(),
+        line: lines + 1,
+        message: String::from("header has no end_header"),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Body
+// ---------------------------------------------------------------------------
+
+/// Reads scalars from the body in declaration order.
+enum Reader<'a> {
+    Ascii { lines: Zip<RangeFrom<usize>, Lines<'a>>, current: SplitWhitespace<'a>, line: usize },
+    Binary { data: &'a [u8], pos: usize, big_endian: bool },
+}
+
+impl Reader<'_> {
+    fn scalar(&mut self, ty: Scalar, path: &Path) -> Result<f64, MeshError> {
+        let value = match *self {
+            Self::Ascii { ref mut lines, ref mut current, ref mut line } => loop {
+                if let Some(token) = current.next() {
+                    break token
+                        .parse::<f64>()
+                        .map_err(|e| format!("expected a number, found '{token}': {e}"));
+                }
+                let Some((number, next)) = lines.next() else {
+                    break Err(String::from("unexpected end of data"));
+                };
+                *line = number;
+                *current = next.split_whitespace();
+            },
+            Self::Binary { data, ref mut pos, big_endian } => {
+                let bytes = data.get(*pos..*pos + ty.size());
+                *pos += ty.size();
+                bytes
+                    .map(|bytes| decode(bytes, ty, big_endian))
+                    .ok_or_else(|| String::from("truncated binary data"))
+            }
+        };
+        value.map_err(|message| self.error(path, message))
+    }
+
+    /// Reads one property, returning a scalar's value or a list's items.
+    fn list_or_scalar(&mut self, property: &Property, path: &Path) -> Result<Vec<f64>, MeshError> {
+        match *property {
+            Property::Scalar { ty, .. } => Ok(vec![self.scalar(ty, path)?]),
+            Property::List { count, item, .. } => {
+                let len = self.scalar(count, path)?;
+                let Some(len) = to_index(len) else {
+                    return Err(self.error(path, format!("bad list length {len}")));
+                };
+                core::iter::repeat_with(|| self.scalar(item, path)).take(len).collect()
+            }
+        }
+    }
+
+    /// How many of `element`'s records to reserve room for. The count comes
+    /// from the header, which may lie, so it is capped by what the body can
+    /// hold: the bytes left over the smallest binary record, or a fixed
+    /// bound for ASCII, whose records have no fixed size.
+    fn capacity(&self, element: &Element) -> usize {
+        const ASCII_RESERVE: usize = 1 << 16;
+        match *self {
+            Self::Ascii { .. } => element.count.min(ASCII_RESERVE),
+            Self::Binary { data, pos, .. } => {
+                let record: usize = element
+                    .properties
+                    .iter()
+                    .map(|property| match *property {
+                        Property::Scalar { ty, .. } => ty.size(),
+                        Property::List { count, .. } => count.size(),
+                    })
+                    .sum();
+                let remaining = data.len().saturating_sub(pos);
+                element.count.min(remaining.checked_div(record).unwrap_or(0))
+            }
+        }
+    }
+
+    /// A parse error at the current ASCII line, or an I/O error for binary
+    /// data, which has no lines.
+    fn error(&self, path: &Path, message: String) -> MeshError {
+        match *self {
+            Self::Ascii { line, .. } => {
+                MeshError::Parse { path: path.to_path_buf(), line, message }
+            }
+            Self::Binary { .. } => MeshError::Io {
+                path: path.to_path_buf(),
+                source: io::Error::new(io::ErrorKind::InvalidData, message),
+            },
+        }
+    }
+}
+
+#[expect(clippy::little_endian_bytes, reason = "binary PLY comes in either byte order")]
+fn decode(bytes: &[u8], ty: Scalar, big_endian: bool) -> f64 {
+    macro_rules! read {
+        ($t:ty) => {{
+            let array = bytes.try_into().unwrap_or_default();
+            if big_endian { <$t>::from_be_bytes(array) } else { <$t>::from_le_bytes(array) }
+        }};
+    }
+    match ty {
+        Scalar::I8 => f64::from(read!(i8)),
+        Scalar::U8 => f64::from(read!(u8)),
+        Scalar::I16 => f64::from(read!(i16)),
+        Scalar::U16 => f64::from(read!(u16)),
+        Scalar::I32 => f64::from(read!(i32)),
+        Scalar::U32 => f64::from(read!(u32)),
+        Scalar::F32 => f64::from(read!(f32)),
+        Scalar::F64 => read!(f64),
+    }
+}
+
+/// `v` as an index, if it is a non-negative integer that fits.
+fn to_index(v: f64) -> Option<usize> {
+    (v >= 0.0 && v.fract() == 0.0 && v <= f64::from(u32::MAX)).then(|| to_usize(v))
+}
+
+#[expect(clippy::as_conversions, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+const fn to_usize(v: f64) -> usize { v as usize }
+
+// ---------------------------------------------------------------------------
+// Elements
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct Vertices {
+    positions: Vec<Point3>,
+    normals: Option<Vec<Vec3>>,
+    uvs: Option<Vec<(f64, f64)>>,
+    colors: Option<Vec<Color3>>,
+}
+
+fn read_vertices(
+    reader: &mut Reader<'_>,
+    element: &Element,
+    path: &Path,
+) -> Result<Vertices, MeshError> {
+    let find = |names: &[&str]| {
+        names.iter().find_map(|&name| element.properties.iter().position(|p| p.name() == name))
+    };
+    let triple = |a, b, c| Some([find(&[a])?, find(&[b])?, find(&[c])?]);
+
+    let Some(position) = triple("x", "y", "z") else {
+        return Err(MeshError::Parse {
+            path: path.to_path_buf(),
+            line: element.line,
+            message: String::from("vertex element has no x, y and z"),
+        });
+    };
+    let normal = triple("nx", "ny", "nz");
+    let uv = find(&["u", "s", "texture_u"]).zip(find(&["v", "t", "texture_v"]));
+    let color = triple("red", "green", "blue");
+    let scale = color.and_then(|[r, ..]| match element.properties.get(r) {
+        Some(&Property::Scalar { ty, .. }) => ty.full_scale(),
+        _ => None,
+    });
+
+    let mut vertices = Vertices {
+        positions: Vec::with_capacity(reader.capacity(element)),
+        normals: normal.map(|_| Vec::with_capacity(reader.capacity(element))),
+        uvs: uv.map(|_| Vec::with_capacity(reader.capacity(element))),
+        colors: color.map(|_| Vec::with_capacity(reader.capacity(element))),
+    };
+    let mut values = vec![0.0; element.properties.len()];
+    for _ in 0..element.count {
+        for (value, property) in values.iter_mut().zip(&element.properties) {
+            *value = reader.list_or_scalar(property, path)?.first().copied().unwrap_or_default();
+        }
+        let at = |i: usize| values.get(i).copied().unwrap_or_default();
+        let vector = |[a, b, c]: [usize; 3]| vec3(at(a), at(b), at(c));
+
+        vertices.positions.push(vector(position));
+        if let Some(normal) = normal
+            && let Some(normals) = vertices.normals.as_mut()
+        {
+            normals.push(vector(normal));
+        }
+        if let Some((u, v)) = uv
+            && let Some(uvs) = vertices.uvs.as_mut()
+        {
+            uvs.push((at(u), at(v)));
+        }
+        if let Some(color) = color
+            && let Some(colors) = vertices.colors.as_mut()
+        {
+            let c = vector(color);
+            colors.push(match scale {
+                Some(scale) => {
+                    let decode = |v: f64| TransferFunction::Srgb.decode(v / scale);
+                    Color3::new(decode(c.x), decode(c.y), decode(c.z))
+                }
+                None => Color3::new(c.x, c.y, c.z),
+            });
+        }
+    }
+    Ok(vertices)
+}
+
+fn read_faces(
+    reader: &mut Reader<'_>,
+    element: &Element,
+    vertex_count: usize,
+    path: &Path,
+) -> Result<Vec<[u32; 3]>, MeshError> {
+    let indices = element.properties.iter().position(|p| match *p {
+        Property::List { ref name, .. } => name == "vertex_indices" || name == "vertex_index",
+        Property::Scalar { .. } => false,
+    });
+    let Some(indices) = indices else {
+        return Err(MeshError::Parse {
+            path: path.to_path_buf(),
+            line: element.line,
+            message: String::from("face element has no vertex_indices list"),
+        });
+    };
+
+    let mut triangles = Vec::with_capacity(reader.capacity(element));
+    for face in 1..=element.count {
+        for (i, property) in element.properties.iter().enumerate() {
+            let values = reader.list_or_scalar(property, path)?;
+            if i != indices {
+                continue;
+            }
+            let polygon = values
+                .iter()
+                .map(|&v| {
+                    let index = to_index(v).filter(|&i| i < vertex_count)?;
+                    u32::try_from(index).ok()
+                })
+                .collect::<Option<Vec<_>>>();
+            let Some(polygon) = polygon else {
+                let message = format!("face {face} refers to a vertex outside 0..{vertex_count}");
+                return Err(reader.error(path, message));
+            };
+            triangles.extend(fan(&polygon));
+        }
+    }
+    Ok(triangles)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng as _;
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::prelude::{Hittable as _, Interval, Lambertian, Ray, point3};
+
+    fn white() -> Arc<dyn Material> { Arc::new(Lambertian::new(Color3::WHITE)) }
+
+    /// A unit square as one quad face, with normals and 8-bit colours, plus
+    /// an element the loader skips.
+    const ASCII: &str = "\
+ply
+format ascii 1.0
+comment made by hand
+element vertex 4
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+element edge 1
+property int vertex1
+property int vertex2
+end_header
+0 0 0 0 0 1 255 0 0
+1 0 0 0 0 1 0 255 0
+1 1 0 0 0 1 0 0 255
+0 1 0 0 0 1 255 255 255
+4 0 1 2 3
+0 1
+";
+
+    #[test]
+    fn scenario_ascii_vertices_faces_and_colours() {
+        let mesh = read_ply(ASCII.as_bytes(), white()).unwrap();
+        assert_eq!(mesh.positions().len(), 4);
+        assert_eq!(mesh.triangle_count(), 2);
+        assert_eq!(mesh.normals(), &[Vec3::Z; 4]);
+        assert_eq!(mesh.colors().first(), Some(&Color3::RED));
+        assert_eq!(mesh.colors().last(), Some(&Color3::WHITE));
+
+        // The vertex colour reaches the hit, and a white Lambertian uses it
+        // as albedo.
+        let ray = Ray::new(point3(0.0001, 0.0001, 1.0), Vec3::NEG_Z, None);
+        let rec = mesh.hit(&ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+        let albedo = rec.color.unwrap();
+        assert!(albedo.r > 0.99 && albedo.g < 0.01 && albedo.b < 0.01);
+        let mut rng = StdRng::seed_from_u64(1);
+        let (attenuation, _) = rec.material.scatter(&mut rng, &ray, &rec).unwrap();
+        assert_eq!(attenuation, albedo);
+    }
+
+    /// The first three vertices of [`ASCII`] as one triangle, in binary.
+    #[expect(clippy::little_endian_bytes, reason = "building binary test files")]
+    fn binary(big_endian: bool) -> Vec<u8> {
+        let order = if big_endian { "binary_big_endian" } else { "binary_little_endian" };
+        let header = format!(
+            "ply\nformat {order} 1.0\nelement vertex 3\nproperty double x\nproperty float \
+             y\nproperty short z\nproperty ushort red\nproperty ushort green\nproperty ushort \
+             blue\nelement face 1\nproperty uchar flags\nproperty list uchar uint \
+             vertex_indices\nend_header\n"
+        );
+        let mut data = header.into_bytes();
+        let mut push =
+            |le: &[u8], be: &[u8]| data.extend_from_slice(if big_endian { be } else { le });
+        for (x, y, color) in
+            [(0.0_f64, 0.0_f32, [0xffff, 0, 0]), (1.0, 0.0, [0; 3]), (1.0, 1.0, [0; 3])]
+        {
+            push(&x.to_le_bytes(), &x.to_be_bytes());
+            push(&y.to_le_bytes(), &y.to_be_bytes());
+            push(&(-2_i16).to_le_bytes(), &(-2_i16).to_be_bytes());
+            for c in color {
+                push(&u16::to_le_bytes(c), &u16::to_be_bytes(c));
+            }
+        }
+        push(&[7, 3], &[7, 3]);
+        for i in 0..3_u32 {
+            push(&i.to_le_bytes(), &i.to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn scenario_binary_in_both_byte_orders() {
+        for big_endian in [false, true] {
+            let mesh = read_ply(&binary(big_endian), white()).unwrap();
+            assert_eq!(mesh.positions(), &[point3(0, 0, -2), point3(1, 0, -2), point3(1, 1, -2)]);
+            assert_eq!(mesh.indices(), &[[0, 1, 2]]);
+            assert_eq!(mesh.colors().first(), Some(&Color3::RED));
+            assert!(mesh.normals().is_empty() && mesh.uvs().is_empty());
+        }
+    }
+
+    #[test]
+    fn scenario_errors() {
+        let ascii = |source: &str| read_ply(source.as_bytes(), white()).err().unwrap().to_string();
+        assert_eq!(ascii("obj\n"), "line 1: not a PLY file");
+        assert_eq!(
+            ascii("ply\nformat ascii 2.0\n"),
+            "line 2: unsupported format 'format ascii 2.0'"
+        );
+        assert_eq!(
+            ascii("ply\nformat ascii 1.0\nelement vertex 1\nproperty half x\n"),
+            "line 4: unknown property type 'half'"
+        );
+        assert_eq!(
+            ascii("ply\nformat ascii 1.0\nelement vertex 1\n"),
+            "line 4: header has no end_header"
+        );
+
+        let bad_index = ASCII.replace("4 0 1 2 3", "3 0 1\n7");
+        assert_eq!(ascii(&bad_index), "line 25: face 1 refers to a vertex outside 0..4");
+        let bad_number = ASCII.replace("1 1 0 0 0 1", "1 x 0 0 0 1");
+        assert!(ascii(&bad_number).starts_with("line 22: expected a number, found 'x'"));
+
+        // Counts far beyond what the body holds fail without reserving room
+        // for them.
+        let huge = "ply\nformat ascii 1.0\nelement vertex 10000000000\nproperty float x\nproperty \
+                    float y\nproperty float z\nend_header\n0 0 0\n";
+        assert_eq!(ascii(huge), "line 8: unexpected end of data");
+        let mut huge = b"ply\nformat binary_little_endian 1.0\nelement vertex 10000000000\n\
+                         property float x\nproperty float y\nproperty float z\nend_header\n"
+            .to_vec();
+        huge.extend_from_slice(&[0; 12]);
+        let error = read_ply(&huge, white()).err().unwrap();
+        assert!(error.to_string().ends_with("truncated binary data"), "{error}");
+
+        let mut truncated = binary(false);
+        truncated.truncate(truncated.len() - 2);
+        let error = read_ply(&truncated, white()).err().unwrap();
+        assert!(matches!(error, MeshError::Io { .. }), "{error}");
+        assert!(error.to_string().ends_with("truncated binary data"));
+    }
+}
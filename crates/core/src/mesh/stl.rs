@@ -0,0 +1,247 @@
+//! STL triangle soups, binary or ASCII.
+//!
+//! STL stores every triangle with its own three corners. The loader welds
+//! corners with identical coordinates into shared vertices, so the result is
+//! an ordinary indexed mesh. Facet normals are ignored in favour of the
+//! winding.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::mesh::{MeshError, TriangleMesh, fan};
+use crate::prelude::{Material, Point3, vec3};
+
+/// Bytes before the first binary facet: an 80-byte header and a `u32` count.
+const BINARY_HEADER: usize = 84;
+/// Bytes per binary facet: normal, three corners and an attribute word.
+const BINARY_FACET: usize = 50;
+
+/// Reads an STL file from memory, binary or ASCII.
+///
+/// Binary files may also begin with `solid`, so a file is only read as ASCII
+/// if its size does not match the binary layout.
+pub fn read_stl(data: &[u8], material: Arc<dyn Material>) -> Result<TriangleMesh, MeshError> {
+    parse_stl(data, Path::new(""), material)
+}
+
+pub(crate) fn parse_stl(
+    data: &[u8],
+    path: &Path,
+    material: Arc<dyn Material>,
+) -> Result<TriangleMesh, MeshError> {
+    let mut welder = Welder::default();
+    if let Some(facets) = binary_facets(data) {
+        for facet in facets {
+            let corner = |i: usize| {
+                let at = |j: usize| f64::from(read_f32(facet, 12 + 12 * i + 4 * j));
+                vec3(at(0), at(1), at(2))
+            };
+            welder.triangle([corner(0), corner(1), corner(2)]);
+        }
+    } else if data.trim_ascii_start().starts_with(b"solid") {
+        parse_ascii(data, path, &mut welder)?;
+    } else {
+        return Err(MeshError::Io {
+            path: path.to_path_buf(),
+            source: io::Error::new(io::ErrorKind::InvalidData, "not an STL file"),
+        });
+    }
+    Ok(TriangleMesh::new(welder.positions, welder.indices, material))
+}
+
+/// The facets of a binary file, if `data` has exactly the size its triangle
+/// count calls for.
+fn binary_facets(data: &[u8]) -> Option<core::slice::ChunksExact<'_, u8>> {
+    let count = data.get(80..BINARY_HEADER)?;
+    let count = usize::try_from(read_u32(count)).ok()?;
+    let body = data.get(BINARY_HEADER..)?;
+    (body.len() == count.checked_mul(BINARY_FACET)?).then(|| body.chunks_exact(BINARY_FACET))
+}
+
+#[expect(clippy::little_endian_bytes, reason = "binary STL is little-endian")]
+fn read_u32(bytes: &[u8]) -> u32 { u32::from_le_bytes(bytes.try_into().unwrap_or_default()) }
+
+#[expect(clippy::little_endian_bytes, reason = "binary STL is little-endian")]
+fn read_f32(facet: &[u8], offset: usize) -> f32 {
+    let bytes = facet.get(offset..offset + 4).unwrap_or_default();
+    f32::from_le_bytes(bytes.try_into().unwrap_or_default())
+}
+
+/// Reads `facet … outer loop / vertex x y z … / endloop / endfacet` blocks.
+/// Loops with more than three vertices are split into a fan.
+fn parse_ascii(data: &[u8], path: &Path, welder: &mut Welder) -> Result<(), MeshError> {
+    let text = String::from_utf8_lossy(data);
+    let mut polygon: Option<Vec<Point3>> = None;
+
+    for (line, content) in (1..).zip(text.lines()) {
+        let error = |message: String| MeshError::Parse { path: path.to_path_buf(), line, message };
+
+        let mut tokens = content.split_whitespace();
+        match (tokens.next(), polygon.as_mut()) {
+            (Some("outer"), None) => polygon = Some(Vec::new()),
+            (Some("vertex"), Some(corners)) => {
+                let mut number = || {
+                    let token = tokens.next().unwrap_or_default();
+                    token
+                        .parse::<f64>()
+                        .map_err(|e| error(format!("expected a number, found '{token}': {e}")))
+                };
+                corners.push(vec3(number()?, number()?, number()?));
+            }
+            (Some("endloop"), Some(corners)) => {
+                if corners.len() < 3 {
+                    return Err(error(format!(
+                        "loop has {} vertices; 3 or more are needed",
+                        corners.len()
+                    )));
+                }
+                welder.polygon(corners);
+                polygon = None;
+            }
+            (Some("vertex" | "endloop"), None) => {
+                return Err(error(String::from("vertex outside an outer loop")));
+            }
+            (Some("outer" | "facet" | "endfacet" | "endsolid" | "solid"), Some(_)) => {
+                return Err(error(String::from("outer loop is missing its endloop")));
+            }
+            _ => {}
+        }
+    }
+    if polygon.is_some() {
+        let line = text.lines().count();
+        return Err(MeshError::Parse {
+            path: path.to_path_buf(),
+            line,
+            message: String::from("file ends inside an outer loop"),
+        });
+    }
+    Ok(())
+}
+
+/// Merges corners with bit-identical coordinates into indexed vertices.
+#[derive(Debug, Default)]
+struct Welder {
+    index_of: HashMap<[u64; 3], u32>,
+    positions: Vec<Point3>,
+    indices: Vec<[u32; 3]>,
+}
+
+impl Welder {
+    fn vertex(&mut self, p: Point3) -> u32 {
+        // Adding zero turns −0 into +0, so the two weld together.
+        let key = [p.x, p.y, p.z].map(|c| (c + 0.0).to_bits());
+        *self.index_of.entry(key).or_insert_with(|| {
+            self.positions.push(p);
+            u32::try_from(self.positions.len() - 1).unwrap_or(u32::MAX)
+        })
+    }
+
+    fn triangle(&mut self, corners: [Point3; 3]) {
+        let [a, b, c] = corners;
+        let ids = [self.vertex(a), self.vertex(b), self.vertex(c)];
+        self.indices.push(ids);
+    }
+
+    /// Fans a loop of any length; triangles skip the scratch `Vec`.
+    fn polygon(&mut self, corners: &[Point3]) {
+        if let [a, b, c] = *corners {
+            self.triangle([a, b, c]);
+        } else {
+            let ids: Vec<u32> = corners.iter().map(|&p| self.vertex(p)).collect();
+            self.indices.extend(fan(&ids));
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Color3, Hittable as _, Lambertian, point3};
+
+    fn grey() -> Arc<dyn Material> { Arc::new(Lambertian::new(Color3::new(0.5, 0.5, 0.5))) }
+
+    /// Two triangles making the unit square, sharing an edge.
+    const LOWER: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]];
+    const UPPER: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+
+    #[expect(clippy::little_endian_bytes, reason = "binary STL is little-endian")]
+    fn binary(header: &[u8]) -> Vec<u8> {
+        let mut data = header.to_vec();
+        data.resize(80, 0);
+        data.extend_from_slice(&2_u32.to_le_bytes());
+        for facet in [LOWER, UPPER] {
+            data.extend([0.0_f32, 0.0, 1.0].iter().flat_map(|c| c.to_le_bytes()));
+            data.extend(facet.iter().flatten().flat_map(|c| c.to_le_bytes()));
+            data.extend_from_slice(&[0, 0]);
+        }
+        data
+    }
+
+    fn assert_square(mesh: &TriangleMesh) {
+        assert_eq!(mesh.positions().len(), 4, "shared corners are welded");
+        assert_eq!(mesh.triangle_count(), 2);
+        let bbox = mesh.bounding_box();
+        assert_eq!((bbox.x.min, bbox.x.max, bbox.y.max), (0.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn scenario_binary() {
+        assert_square(&read_stl(&binary(b"exported by a CAD tool"), grey()).unwrap());
+        // A binary header may start with "solid" too.
+        assert_square(&read_stl(&binary(b"solid but really binary"), grey()).unwrap());
+    }
+
+    #[test]
+    fn scenario_ascii() {
+        let source = "\
+solid square
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 1 1 0
+    endloop
+  endfacet
+  facet normal 0 0 1
+    outer loop
+      vertex -0 0 0
+      vertex 1 1 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid square
+";
+        let mesh = read_stl(source.as_bytes(), grey()).unwrap();
+        assert_square(&mesh);
+        assert!(mesh.positions().contains(&point3(0, 1, 0)));
+    }
+
+    #[test]
+    fn scenario_errors() {
+        let message = |source: &str| read_stl(source.as_bytes(), grey()).err().unwrap().to_string();
+        assert!(
+            message("solid\nouter loop\nvertex 0 0 x\n")
+                .starts_with("line 3: expected a number, found 'x'")
+        );
+        assert_eq!(
+            message("solid\nouter loop\nvertex 0 0 0\nendloop\n"),
+            "line 4: loop has 1 vertices; 3 or more are needed"
+        );
+        assert_eq!(message("solid\nvertex 0 0 0\n"), "line 2: vertex outside an outer loop");
+        assert_eq!(
+            message("solid\nouter loop\nvertex 0 0 0\n"),
+            "line 3: file ends inside an outer loop"
+        );
+        assert!(message("PK\u{3}\u{4}").ends_with("not an STL file"));
+
+        let mut truncated = binary(b"");
+        truncated.pop();
+        assert!(read_stl(&truncated, grey()).is_err());
+    }
+}
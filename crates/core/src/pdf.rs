@@ -0,0 +1,266 @@
+//! Probability densities over directions, for importance sampling.
+//!
+//! Every [`Pdf`] can both draw a direction and report the density of any
+//! direction, measured per unit solid angle. Dividing a sample's contribution
+//! by that density keeps the estimate unbiased however the directions were
+//! drawn, so samples can be steered towards lights or along a BRDF.
+
+use core::f64::consts::PI;
+
+use rand::prelude::Rng;
+use shared::random;
+
+use crate::prelude::{Hittable, Onb, Point3, Vec3};
+
+pub trait Pdf {
+    /// The density of `direction`, which need not be unit length.
+    fn value(&self, direction: Vec3) -> f64;
+
+    /// Draws a direction with this density. Not necessarily unit length.
+    fn generate(&self, rng: &mut dyn Rng) -> Vec3;
+}
+
+// ---------------------------------------------------------------------------
+// SpherePdf
+// ---------------------------------------------------------------------------
+
+/// Uniform over the whole sphere of directions.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpherePdf;
+
+impl Pdf for SpherePdf {
+    fn value(&self, _: Vec3) -> f64 { 1.0 / (4.0 * PI) }
+
+    fn generate(&self, rng: &mut dyn Rng) -> Vec3 { Vec3::random_unit(rng) }
+}
+
+// ---------------------------------------------------------------------------
+// CosinePdf
+// ---------------------------------------------------------------------------
+
+/// Proportional to the cosine of the angle to a normal, zero below it: the
+/// ideal density for a Lambertian surface.
+#[derive(Clone, Copy, Debug)]
+pub struct CosinePdf {
+    uvw: Onb,
+}
+
+impl CosinePdf {
+    #[inline]
+    #[must_use]
+    pub fn new(normal: Vec3) -> Self { Self { uvw: Onb::new(normal) } }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: Vec3) -> f64 {
+        let cosine = direction.unit().dot(self.uvw.w);
+        (cosine / PI).max(0.0)
+    }
+
+    fn generate(&self, rng: &mut dyn Rng) -> Vec3 {
+        self.uvw.transform(Vec3::random_cosine_direction(rng))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HittablePdf
+// ---------------------------------------------------------------------------
+
+/// Directions from `origin` towards points on some geometry, as sampled by
+/// [`Hittable::random`]. Used to aim rays at lights.
+///
+/// The geometry should be [sampleable](Hittable::is_sampleable); otherwise
+/// every direction has zero density.
+#[derive(Clone, Copy)]
+pub struct HittablePdf<'a> {
+    objects: &'a dyn Hittable,
+    origin: Point3,
+}
+
+impl<'a> HittablePdf<'a> {
+    #[inline]
+    #[must_use]
+    pub const fn new(objects: &'a dyn Hittable, origin: Point3) -> Self { Self { objects, origin } }
+}
+
+impl Pdf for HittablePdf<'_> {
+    fn value(&self, direction: Vec3) -> f64 {
+        self.objects.pdf_value(self.origin, direction).unwrap_or(0.0)
+    }
+
+    fn generate(&self, rng: &mut dyn Rng) -> Vec3 {
+        // A zero vector has no density, so it never counts.
+        self.objects.random(rng, self.origin).unwrap_or(Vec3::ZERO)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MixturePdf
+// ---------------------------------------------------------------------------
+
+/// An even mix of two densities: each sample comes from either one with
+/// equal probability.
+#[derive(Clone, Copy)]
+pub struct MixturePdf<'a> {
+    pdfs: [&'a dyn Pdf; 2],
+}
+
+impl<'a> MixturePdf<'a> {
+    #[inline]
+    #[must_use]
+    pub const fn new(a: &'a dyn Pdf, b: &'a dyn Pdf) -> Self { Self { pdfs: [a, b] } }
+}
+
+impl Pdf for MixturePdf<'_> {
+    fn value(&self, direction: Vec3) -> f64 {
+        let [a, b] = self.pdfs;
+        0.5 * a.value(direction) + 0.5 * b.value(direction)
+    }
+
+    fn generate(&self, rng: &mut dyn Rng) -> Vec3 {
+        let [a, b] = self.pdfs;
+        if random(rng) < 0.5 { a.generate(rng) } else { b.generate(rng) }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Multiple importance sampling
+// ---------------------------------------------------------------------------
+
+/// The power heuristic (β = 2) weight for a sample drawn with density
+/// `pdf` when `other` could have drawn it too. The two weights of a
+/// direction always sum to one, so both strategies can be used at once
+/// without counting any light twice.
+#[inline]
+#[must_use]
+pub fn power_heuristic(pdf: f64, other: f64) -> f64 {
+    let (a, b) = (pdf * pdf, other * other);
+    if a + b > 0.0 { a / (a + b) } else { 0.0 }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::SeedableRng as _;
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::prelude::{
+        Color3,
+        Hittables,
+        Instance,
+        Lambertian,
+        Material,
+        Quad,
+        Sphere,
+        Transform,
+        Triangle,
+        TriangleMesh,
+        point3,
+        vec3,
+    };
+
+    fn grey() -> Arc<dyn Material> { Arc::new(Lambertian::new(Color3::new(0.5, 0.5, 0.5))) }
+
+    /// Monte Carlo estimate of ∫ value dω over the sphere, drawing uniform
+    /// directions. A density should integrate to one.
+    fn integral(pdf: &dyn Pdf, rng: &mut dyn Rng) -> f64 {
+        const N: u32 = 200_000;
+        let total: f64 = core::iter::repeat_with(|| pdf.value(Vec3::random_unit(rng)))
+            .take(N.try_into().unwrap())
+            .sum();
+        4.0 * PI * total / f64::from(N)
+    }
+
+    /// Checks that `pdf` generates directions it gives positive density, and
+    /// that its density integrates to one.
+    fn assert_normalised(pdf: &dyn Pdf, rng: &mut dyn Rng) {
+        for _ in 0..1000 {
+            let direction = pdf.generate(rng);
+            assert!(pdf.value(direction) > 0.0, "generated {direction} has no density");
+        }
+        let total = integral(pdf, rng);
+        assert!((total - 1.0).abs() < 0.03, "integrates to {total}");
+    }
+
+    #[test]
+    fn scenario_sphere_and_cosine() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_normalised(&SpherePdf, &mut rng);
+
+        let cosine = CosinePdf::new(vec3(1, 2, -1));
+        assert_normalised(&cosine, &mut rng);
+        assert!(cosine.value(vec3(-1, -2, 1)) <= 0.0);
+        shared::assert_fuzzy_eq!(cosine.value(vec3(2, 4, -2)), 1.0 / PI);
+    }
+
+    #[test]
+    fn scenario_hittable_densities() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let origin = point3(0.2, -0.3, 0.1);
+
+        let quad = Quad::new(point3(-1, -1, 3), vec3(2, 0, 0), vec3(0, 3, 1), grey());
+        assert_normalised(&HittablePdf::new(&quad, origin), &mut rng);
+
+        let sphere = Sphere::new(point3(2, 1, -2), None, 1.5, grey());
+        assert_normalised(&HittablePdf::new(&sphere, origin), &mut rng);
+
+        let triangle =
+            Triangle::new(point3(-2, 0, -1), point3(1, -2, -2), point3(0, 2, -1), grey());
+        assert_normalised(&HittablePdf::new(&triangle, origin), &mut rng);
+
+        // A squashed, rotated sphere: the density must account for the
+        // stretch of the transform.
+        let instance = Instance::new(
+            Arc::new(Sphere::new(Point3::ZERO, None, 1.0, grey())),
+            Transform::translate(vec3(0, 0, 3))
+                * Transform::rotate(vec3(1, 1, 0), 40.0)
+                * Transform::scale(vec3(2.0, 0.5, 1.0)),
+        );
+        assert_normalised(&HittablePdf::new(&instance, origin), &mut rng);
+
+        // A closed tetrahedron: most directions that reach it cross two
+        // faces, and both could have been sampled.
+        let corners = vec![point3(-1, 0, 3), point3(1, 0, 3), point3(0, 2, 3), point3(0, 1, 5)];
+        let faces = vec![[0, 1, 2], [0, 3, 1], [1, 3, 2], [2, 3, 0]];
+        let mesh = TriangleMesh::new(corners, faces, grey());
+        assert_normalised(&HittablePdf::new(&mesh, origin), &mut rng);
+
+        // Objects that can't be sampled are skipped.
+        let objects: [Arc<dyn Hittable>; 3] =
+            [Arc::new(quad), Arc::new(Hittables::new()), Arc::new(sphere)];
+        let lights: Hittables = objects.into_iter().collect();
+        assert_normalised(&HittablePdf::new(&lights, origin), &mut rng);
+        assert!(lights.is_sampleable());
+        assert!(!Hittables::new().is_sampleable());
+        assert!(Hittables::new().pdf_value(origin, Vec3::X).is_none());
+    }
+
+    #[test]
+    fn scenario_mixture() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let quad = Quad::new(point3(-1, 2, -1), vec3(2, 0, 0), vec3(0, 0, 2), grey());
+        let towards_light = HittablePdf::new(&quad, Point3::ZERO);
+        let cosine = CosinePdf::new(Vec3::Y);
+        let mixture = MixturePdf::new(&towards_light, &cosine);
+        assert_normalised(&mixture, &mut rng);
+        shared::assert_fuzzy_eq!(
+            mixture.value(Vec3::Y),
+            0.5 * towards_light.value(Vec3::Y) + 0.5 / PI
+        );
+    }
+
+    #[test]
+    fn scenario_power_heuristic() {
+        shared::assert_fuzzy_eq!(power_heuristic(1.0, 1.0), 0.5);
+        shared::assert_fuzzy_eq!(power_heuristic(3.0, 1.0) + power_heuristic(1.0, 3.0), 1.0);
+        shared::assert_fuzzy_eq!(power_heuristic(3.0, 1.0), 0.9);
+        shared::assert_fuzzy_eq!(power_heuristic(2.0, 0.0), 1.0);
+        shared::assert_fuzzy_eq!(power_heuristic(0.0, 0.0), 0.0);
+    }
+}
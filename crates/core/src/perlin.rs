@@ -0,0 +1,162 @@
+use rand::prelude::Rng;
+use shared::random_index;
+
+use crate::prelude::{Point3, Vec3, vec3};
+
+/// Lattice period; noise repeats every `POINT_COUNT` units on each axis.
+const POINT_COUNT: usize = 256;
+/// [`POINT_COUNT`] as a lattice coordinate, for wrapping cell indices.
+#[expect(clippy::as_conversions, clippy::cast_possible_wrap)]
+const PERIOD: i64 = POINT_COUNT as i64;
+
+/// Ken Perlin's gradient noise.
+///
+/// Random unit gradients sit on an integer lattice and are blended with a
+/// Hermite-smoothed trilinear interpolation. All randomness is drawn at
+/// construction, so a seeded RNG gives repeatable noise.
+#[derive(Clone, Debug)]
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    #[must_use]
+    pub fn new(rng: &mut dyn Rng) -> Self {
+        let gradients = core::iter::repeat_with(|| Vec3::random_range(rng, -1.0, 1.0).unit())
+            .take(POINT_COUNT)
+            .collect();
+        Self {
+            gradients,
+            perm_x: permutation(rng),
+            perm_y: permutation(rng),
+            perm_z: permutation(rng),
+        }
+    }
+
+    /// Noise at `p`, in `[-1, 1]` and zero at every lattice point.
+    #[must_use]
+    pub fn noise(&self, p: Point3) -> f64 {
+        let (i, u) = split(p.x);
+        let (j, v) = split(p.y);
+        let (k, w) = split(p.z);
+
+        let mut c = [[[Vec3::ZERO; 2]; 2]; 2];
+        for (di, plane) in (0..).zip(&mut c) {
+            for (dj, row) in (0..).zip(plane) {
+                for (dk, corner) in (0..).zip(row) {
+                    *corner = self.gradient(i + di, j + dj, k + dk);
+                }
+            }
+        }
+        interpolate(&c, u, v, w)
+    }
+
+    /// Sum of `octaves` layers of noise, each at double the frequency and
+    /// half the amplitude of the last, folded to be non-negative.
+    #[must_use]
+    pub fn turbulence(&self, p: Point3, octaves: u32) -> f64 {
+        let mut accum = 0.0;
+        let mut p = p;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            accum += weight * self.noise(p);
+            weight *= 0.5;
+            p = 2.0 * p;
+        }
+        accum.abs()
+    }
+
+    fn gradient(&self, i: i64, j: i64, k: i64) -> Vec3 {
+        let lookup = |perm: &[usize], n: i64| {
+            let n = usize::try_from(n.rem_euclid(PERIOD)).unwrap_or(0);
+            perm.get(n).copied().unwrap_or(0)
+        };
+        let hash = lookup(&self.perm_x, i) ^ lookup(&self.perm_y, j) ^ lookup(&self.perm_z, k);
+        self.gradients.get(hash).copied().unwrap_or(Vec3::ZERO)
+    }
+}
+
+/// A random permutation of `0..POINT_COUNT` (Fisher–Yates).
+fn permutation(rng: &mut dyn Rng) -> Vec<usize> {
+    let mut perm: Vec<usize> = (0..POINT_COUNT).collect();
+    for i in (1..POINT_COUNT).rev() {
+        perm.swap(i, random_index(rng, i + 1));
+    }
+    perm
+}
+
+/// Splits `x` into its lattice cell and the fractional offset within it.
+fn split(x: f64) -> (i64, f64) {
+    let floor = x.floor();
+    (to_i64(floor), x - floor)
+}
+
+/// Trilinear blend of the corner gradients' dot products, with Hermite
+/// smoothing of the weights to hide the lattice.
+fn interpolate(c: &[[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+    let [uu, vv, ww] = [u, v, w].map(|t| t * t * (3.0 - 2.0 * t));
+    let mut accum = 0.0;
+    for (i, plane) in (0..2_u8).zip(c) {
+        for (j, row) in (0..2_u8).zip(plane) {
+            for (k, gradient) in (0..2_u8).zip(row) {
+                let (fi, fj, fk) = (f64::from(i), f64::from(j), f64::from(k));
+                let weight = vec3(u - fi, v - fj, w - fk);
+                accum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                    * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                    * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                    * gradient.dot(weight);
+            }
+        }
+    }
+    accum
+}
+
+#[expect(clippy::as_conversions, clippy::cast_possible_truncation)]
+const fn to_i64(v: f64) -> i64 { v as i64 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng as _;
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::prelude::point3;
+
+    #[test]
+    fn scenario_seeded_noise_is_repeatable() {
+        let a = Perlin::new(&mut StdRng::seed_from_u64(7));
+        let b = Perlin::new(&mut StdRng::seed_from_u64(7));
+        let p = point3(1.3, -4.7, 0.25);
+        assert_eq!(a.noise(p).to_bits(), b.noise(p).to_bits());
+        assert_eq!(a.turbulence(p, 7).to_bits(), b.turbulence(p, 7).to_bits());
+    }
+
+    #[test]
+    fn scenario_noise_vanishes_on_the_lattice_and_stays_bounded() {
+        let perlin = Perlin::new(&mut StdRng::seed_from_u64(1));
+        shared::assert_fuzzy_eq!(perlin.noise(point3(3.0, -2.0, 17.0)), 0.0);
+
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..1000 {
+            let p = 50.0 * Vec3::random_range(&mut rng, -1.0, 1.0);
+            let n = perlin.noise(p);
+            assert!((-1.0..=1.0).contains(&n), "noise({p}) = {n}");
+            assert!(perlin.turbulence(p, 7) >= 0.0);
+        }
+    }
+
+    #[test]
+    fn scenario_noise_is_continuous() {
+        let perlin = Perlin::new(&mut StdRng::seed_from_u64(3));
+        let p = point3(0.999_999, 0.5, 0.5);
+        let q = point3(1.000_001, 0.5, 0.5);
+        assert!((perlin.noise(p) - perlin.noise(q)).abs() < 1e-4);
+    }
+}
@@ -1,10 +1,81 @@
 pub use crate::aabb::AABB;
+pub use crate::animation::{Animated, AnimatedTransform, Keyframe};
 pub use crate::axis::{Axis, Channel};
+pub use crate::background::{Background, EnvironmentMap};
+pub use crate::bvh::{BvhNode, LinearBvh, SplitMethod};
 pub use crate::camera::Camera;
-pub use crate::color::{Color3, color};
-pub use crate::geometry::{Point3, Vec3, point3, vec3};
+pub use crate::color::{
+    AcesFitted,
+    Color3,
+    Exposure,
+    Hable,
+    Reinhard,
+    ReinhardExtended,
+    ToneMapper,
+    TransferFunction,
+    color,
+};
+pub use crate::geometry::{Onb, Point3, Quaternion, Transform, Vec3, point3, vec3};
 pub use crate::hittable::{HitRecord, Hittable, Hittables};
+pub use crate::image::{
+    ExrCompression,
+    ExrWriter,
+    HdrWriter,
+    Image,
+    ImageError,
+    ImageWriter,
+    PfmWriter,
+    PngBitDepth,
+    PngWriter,
+    PpmFormat,
+    PpmWriter,
+};
+pub use crate::instance::Instance;
+pub use crate::integrator::{
+    AmbientOcclusion,
+    DebugView,
+    DirectLighting,
+    Integrator,
+    LightSampling,
+    PathIntegrator,
+    PathTracing,
+    Scene,
+};
 pub use crate::interval::{Interval, interval};
-pub use crate::material::{Dielectric, Lambertian, Material, Metal};
+pub use crate::material::{
+    Dielectric,
+    DiffuseLight,
+    Isotropic,
+    Lambertian,
+    Lobe,
+    Material,
+    Metal,
+    ScatterRecord,
+};
+pub use crate::medium::{ConstantMedium, DensityGrid, GridError, GridMedium};
+pub use crate::mesh::{
+    MaterialLibrary,
+    MeshError,
+    Obj,
+    ObjObject,
+    TriangleMesh,
+    load_mtl,
+    read_ply,
+    read_stl,
+};
+pub use crate::pdf::{CosinePdf, HittablePdf, MixturePdf, Pdf, SpherePdf, power_heuristic};
+pub use crate::perlin::Perlin;
+pub use crate::quad::{Quad, make_box};
 pub use crate::ray::Ray;
 pub use crate::sphere::Sphere;
+pub use crate::texture::{
+    Checker,
+    ImageTexture,
+    Marble,
+    NoiseTexture,
+    SolidColor,
+    Texture,
+    UvChecker,
+    Wood,
+};
+pub use crate::triangle::{Culling, Triangle};
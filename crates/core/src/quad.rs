@@ -0,0 +1,229 @@
+use std::sync::Arc;
+
+use rand::prelude::Rng;
+use shared::random;
+
+use crate::prelude::{
+    AABB,
+    HitRecord,
+    Hittable,
+    Hittables,
+    Interval,
+    Material,
+    Point3,
+    Ray,
+    Vec3,
+    interval,
+    point3,
+    vec3,
+};
+
+/// A parallelogram with corner `q` and edges `u` and `v`.
+///
+/// The front face is the side `u × v` points to. Surface coordinates run
+/// from `(0, 0)` at `q` to `(1, 1)` at `q + u + v`.
+pub struct Quad {
+    pub q: Point3,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub material: Arc<dyn Material>,
+    /// Unit normal of the plane.
+    normal: Vec3,
+    /// Plane offset: `normal · p = d` for every `p` on the plane.
+    d: f64,
+    /// `n / (n · n)` with `n = u × v`; projects hits onto the `(u, v)` basis.
+    w: Vec3,
+    bbox: AABB,
+}
+
+impl Quad {
+    #[must_use]
+    pub fn new(q: Point3, u: Vec3, v: Vec3, material: Arc<dyn Material>) -> Self {
+        let n = u.cross(v);
+        let normal = n.unit();
+        let d = normal.dot(q);
+        let w = n / n.dot(n);
+
+        // Both diagonals, so the box is right whichever way the edges point.
+        // `AABB::from` pads the flat axis of an axis-aligned quad.
+        let bbox = AABB::from((AABB::from((q, q + u + v)), AABB::from((q + u, q + v))));
+
+        Self { q, u, v, material, normal, d, w, bbox }
+    }
+}
+
+impl Hittable for Quad {
+    fn bounding_box(&self) -> AABB { self.bbox }
+
+    fn hit(&self, ray: &Ray, t: Interval) -> Option<HitRecord> {
+        // No hit if the ray is parallel to the plane.
+        let denom = self.normal.dot(ray.direction);
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t_hit = (self.d - self.normal.dot(ray.origin)) / denom;
+        if !t.contains(t_hit) {
+            return None;
+        }
+
+        // Express the hit in the (u, v) basis and reject anything outside the
+        // unit square.
+        let p = ray.at(t_hit);
+        let planar = p - self.q;
+        let alpha = self.w.dot(planar.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar));
+        let unit = Interval::new(0.0, 1.0);
+        if !unit.contains(alpha) || !unit.contains(beta) {
+            return None;
+        }
+
+        let mut record = HitRecord {
+            p,
+            normal: self.normal, // overwritten below
+            t: t_hit,
+            u: alpha,
+            v: beta,
+            color: None,
+            material: Arc::clone(&self.material),
+            is_front_face: false, // overwritten below
+        };
+        record.set_face_normal(ray, self.normal);
+        Some(record)
+    }
+
+    fn is_sampleable(&self) -> bool { true }
+
+    /// Points are sampled uniformly by area, so the density per solid angle
+    /// is distance² / (cos θ × area).
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> Option<f64> {
+        let ray = Ray::new(origin, direction, None);
+        let Some(rec) = self.hit(&ray, interval(0.001, f64::INFINITY)) else {
+            return Some(0.0);
+        };
+        let distance_squared = rec.t * rec.t * direction.length_squared();
+        let cosine = direction.dot(rec.normal).abs() / direction.length();
+        Some(distance_squared / (cosine * self.u.cross(self.v).length()))
+    }
+
+    fn random(&self, rng: &mut dyn Rng, origin: Point3) -> Option<Vec3> {
+        Some(self.q + random(rng) * self.u + random(rng) * self.v - origin)
+    }
+}
+
+/// The six outward-facing quads of the axis-aligned box with opposite
+/// corners `a` and `b`.
+#[must_use]
+pub fn make_box(a: Point3, b: Point3, material: &Arc<dyn Material>) -> Hittables {
+    let min = point3(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+    let max = point3(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+
+    let dx = vec3(max.x - min.x, 0.0, 0.0);
+    let dy = vec3(0.0, max.y - min.y, 0.0);
+    let dz = vec3(0.0, 0.0, max.z - min.z);
+
+    [
+        (point3(min.x, min.y, max.z), dx, dy),  // front
+        (point3(max.x, min.y, max.z), -dz, dy), // right
+        (point3(max.x, min.y, min.z), -dx, dy), // back
+        (point3(min.x, min.y, min.z), dz, dy),  // left
+        (point3(min.x, max.y, max.z), dx, -dz), // top
+        (point3(min.x, min.y, min.z), dx, dz),  // bottom
+    ]
+    .into_iter()
+    .map(|(q, u, v)| -> Arc<dyn Hittable> { Arc::new(Quad::new(q, u, v, Arc::clone(material))) })
+    .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{Lambertian, color};
+
+    fn material() -> Arc<dyn Material> { Arc::new(Lambertian::new(color(0.5, 0.5, 0.5))) }
+
+    /// A unit square in the z = 0 plane, facing +z.
+    fn square() -> Quad { Quad::new(Point3::ZERO, Vec3::X, Vec3::Y, material()) }
+
+    const ANY: Interval = Interval::new(0.001, f64::INFINITY);
+
+    #[test]
+    fn scenario_interior_hit_reports_uv_and_normal() {
+        let ray = Ray::new(point3(0.25, 0.75, 5.0), Vec3::NEG_Z, None);
+        let rec = square().hit(&ray, ANY).unwrap();
+
+        shared::assert_fuzzy_eq!(rec.t, 5.0);
+        shared::assert_fuzzy_eq!(rec.u, 0.25);
+        shared::assert_fuzzy_eq!(rec.v, 0.75);
+        assert_eq!(rec.normal, Vec3::Z);
+        assert!(rec.is_front_face);
+    }
+
+    #[test]
+    fn scenario_back_face_flips_the_normal() {
+        let ray = Ray::new(point3(0.5, 0.5, -1.0), Vec3::Z, None);
+        let rec = square().hit(&ray, ANY).unwrap();
+        assert_eq!(rec.normal, Vec3::NEG_Z);
+        assert!(!rec.is_front_face);
+    }
+
+    #[test]
+    fn scenario_misses() {
+        let quad = square();
+        // Outside the parallelogram.
+        assert!(quad.hit(&Ray::new(point3(1.5, 0.5, 1.0), Vec3::NEG_Z, None), ANY).is_none());
+        assert!(quad.hit(&Ray::new(point3(0.5, -0.1, 1.0), Vec3::NEG_Z, None), ANY).is_none());
+        // Parallel to the plane.
+        assert!(quad.hit(&Ray::new(point3(0.5, 0.5, 0.0), Vec3::X, None), ANY).is_none());
+        // Plane behind the ray, or outside the interval.
+        assert!(quad.hit(&Ray::new(point3(0.5, 0.5, 1.0), Vec3::Z, None), ANY).is_none());
+        let ray = Ray::new(point3(0.5, 0.5, 5.0), Vec3::NEG_Z, None);
+        assert!(quad.hit(&ray, interval(0.0, 4.0)).is_none());
+    }
+
+    #[test]
+    fn scenario_parallelogram_coordinates() {
+        // Sheared: v leans along x.
+        let quad = Quad::new(Point3::ZERO, vec3(2.0, 0.0, 0.0), vec3(1.0, 1.0, 0.0), material());
+        let rec = quad.hit(&Ray::new(point3(2.5, 0.5, 1.0), Vec3::NEG_Z, None), ANY).unwrap();
+        shared::assert_fuzzy_eq!(rec.u, 1.0);
+        shared::assert_fuzzy_eq!(rec.v, 0.5);
+        assert!(quad.hit(&Ray::new(point3(0.25, 0.5, 1.0), Vec3::NEG_Z, None), ANY).is_none());
+    }
+
+    #[test]
+    fn scenario_flat_bounding_box_is_padded() {
+        let bbox = square().bounding_box();
+        assert!(bbox.z.size() > 0.0);
+        assert_eq!((bbox.x.min, bbox.x.max, bbox.y.max), (0.0, 1.0, 1.0));
+
+        let ray = Ray::new(point3(0.5, 0.5, 1.0), Vec3::NEG_Z, None);
+        assert!(bbox.hit(&ray, ANY));
+    }
+
+    #[test]
+    fn scenario_box_faces_point_outwards() {
+        let cube = make_box(point3(1.0, 1.0, 1.0), point3(-1.0, -1.0, -1.0), &material());
+        assert_eq!(cube.len(), 6);
+
+        let bbox = cube.bounding_box();
+        for axis in [bbox.x, bbox.y, bbox.z] {
+            // Each face's box is padded, so the union is a hair larger.
+            assert!(axis.contains(-1.0) && axis.contains(1.0) && axis.size() < 2.001);
+        }
+
+        // Rays from outside along each axis hit a front face at distance 4.
+        for axis in Vec3::AXES {
+            for dir in [axis, -axis] {
+                let ray = Ray::new(-5.0 * dir, dir, None);
+                let rec = cube.hit(&ray, ANY).unwrap();
+                shared::assert_fuzzy_eq!(rec.t, 4.0);
+                assert!(rec.is_front_face, "face hit along {dir} is inward-facing");
+            }
+        }
+    }
+}
@@ -1,6 +1,20 @@
+use core::f64::consts::{PI, TAU};
 use std::sync::Arc;
 
-use crate::prelude::{AABB, HitRecord, Hittable, Interval, Material, Point3, Ray, Vec3};
+use rand::prelude::Rng;
+
+use crate::prelude::{
+    AABB,
+    HitRecord,
+    Hittable,
+    Interval,
+    Material,
+    Onb,
+    Point3,
+    Ray,
+    Vec3,
+    interval,
+};
 
 /// A sphere — the only primitive in Book 1.
 pub struct Sphere {
@@ -75,10 +89,14 @@ impl Hittable for Sphere {
 
         let p = ray.at(t_hit);
         let outward_normal = (p - center) / self.radius;
+        let (u, v) = sphere_uv(outward_normal);
 
         let mut record = HitRecord {
             p,
             t: t_hit,
+            u,
+            v,
+            color: None,
             normal: outward_normal, // overwritten below
             is_front_face: false,   // overwritten below
             material: Arc::clone(&self.material),
@@ -87,4 +105,44 @@ impl Hittable for Sphere {
 
         Some(record)
     }
+
+    fn is_sampleable(&self) -> bool { true }
+
+    /// Directions are sampled uniformly over the cone the sphere subtends,
+    /// so the density is one over its solid angle. Moving spheres are
+    /// sampled where they are at time zero.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> Option<f64> {
+        let ray = Ray::new(origin, direction, None);
+        if self.hit(&ray, interval(0.001, f64::INFINITY)).is_none() {
+            return Some(0.0);
+        }
+        let distance_squared = (self.center.origin - origin).length_squared();
+        let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared).max(0.0).sqrt();
+        let solid_angle = TAU * (1.0 - cos_theta_max);
+        Some(1.0 / solid_angle)
+    }
+
+    fn random(&self, rng: &mut dyn Rng, origin: Point3) -> Option<Vec3> {
+        let direction = self.center.origin - origin;
+        let distance_squared = direction.length_squared();
+        let uvw = Onb::new(direction);
+        Some(uvw.transform(Vec3::random_to_sphere(rng, self.radius, distance_squared)))
+    }
+}
+
+/// Spherical `(u, v)` for a point `p` on the unit sphere.
+///
+/// `u` is the angle around the Y axis from X = −1, and `v` the angle from
+/// Y = −1 up to Y = +1, both mapped to `[0, 1]`:
+///
+/// ```text
+/// <1 0 0> → <0.50 0.50>    <−1  0  0> → <0.00 0.50>
+/// <0 1 0> → <0.50 1.00>    < 0 −1  0> → <0.50 0.00>
+/// <0 0 1> → <0.25 0.50>    < 0  0 −1> → <0.75 0.50>
+/// ```
+#[must_use]
+pub fn sphere_uv(p: Vec3) -> (f64, f64) {
+    let theta = (-p.y).clamp(-1.0, 1.0).acos();
+    let phi = (-p.z).atan2(p.x) + PI;
+    (phi / TAU, theta / PI)
 }
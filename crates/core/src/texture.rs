@@ -0,0 +1,389 @@
+use core::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::image::ImageError;
+use crate::perlin::Perlin;
+use crate::prelude::{Color3, Image, Point3, TransferFunction};
+
+/// A colour that varies over a surface.
+///
+/// `(u, v)` are the surface coordinates from the
+/// [`HitRecord`](crate::hittable::HitRecord); `p` is the world-space hit point,
+/// for solid (3-D) textures.
+pub trait Texture: fmt::Debug + Send + Sync {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color3;
+}
+
+// ---------------------------------------------------------------------------
+// SolidColor
+// ---------------------------------------------------------------------------
+
+/// The same colour everywhere.
+#[derive(Clone, Copy, Debug)]
+pub struct SolidColor {
+    pub albedo: Color3,
+}
+
+impl SolidColor {
+    #[inline]
+    #[must_use]
+    pub const fn new(albedo: Color3) -> Self { Self { albedo } }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: Point3) -> Color3 { self.albedo }
+}
+
+impl From<Color3> for SolidColor {
+    fn from(albedo: Color3) -> Self { Self::new(albedo) }
+}
+
+// ---------------------------------------------------------------------------
+// Checker (spatial)
+// ---------------------------------------------------------------------------
+
+/// A 3-D checkerboard of cubes `scale` units across, alternating between
+/// two textures. Solid, so it needs no UVs.
+#[derive(Clone, Debug)]
+pub struct Checker {
+    inv_scale: f64,
+    pub even: Arc<dyn Texture>,
+    pub odd: Arc<dyn Texture>,
+}
+
+impl Checker {
+    #[must_use]
+    pub fn new(scale: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        Self { inv_scale: 1.0 / scale, even, odd }
+    }
+
+    #[must_use]
+    pub fn from_colors(scale: f64, even: Color3, odd: Color3) -> Self {
+        Self::new(scale, Arc::new(SolidColor::new(even)), Arc::new(SolidColor::new(odd)))
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color3 {
+        let cell = |x: f64| (self.inv_scale * x).floor();
+        let sum = cell(p.x) + cell(p.y) + cell(p.z);
+        if sum.rem_euclid(2.0) == 0.0 { self.even.value(u, v, p) } else { self.odd.value(u, v, p) }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// UvChecker
+// ---------------------------------------------------------------------------
+
+/// A checkerboard in texture space with `columns × rows` squares over the
+/// unit `(u, v)` square.
+#[derive(Clone, Debug)]
+pub struct UvChecker {
+    pub columns: f64,
+    pub rows: f64,
+    pub even: Arc<dyn Texture>,
+    pub odd: Arc<dyn Texture>,
+}
+
+impl UvChecker {
+    #[must_use]
+    pub fn new(columns: f64, rows: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        Self { columns, rows, even, odd }
+    }
+
+    #[must_use]
+    pub fn from_colors(columns: f64, rows: f64, even: Color3, odd: Color3) -> Self {
+        Self::new(columns, rows, Arc::new(SolidColor::new(even)), Arc::new(SolidColor::new(odd)))
+    }
+}
+
+impl Texture for UvChecker {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color3 {
+        let sum = (u * self.columns).floor() + (v * self.rows).floor();
+        if sum.rem_euclid(2.0) == 0.0 { self.even.value(u, v, p) } else { self.odd.value(u, v, p) }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ImageTexture
+// ---------------------------------------------------------------------------
+
+/// How an [`ImageTexture`] reconstructs colour between texel centres.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Filter {
+    /// The texel containing the lookup point.
+    #[default]
+    Nearest,
+    /// A weighted blend of the four nearest texels.
+    Bilinear,
+}
+
+/// How an [`ImageTexture`] treats coordinates outside `[0, 1]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Wrap {
+    /// Tile the image.
+    Repeat,
+    /// Stretch the edge texels outwards.
+    #[default]
+    Clamp,
+    /// Tile the image, flipping every other copy so edges meet seamlessly.
+    Mirror,
+}
+
+impl Wrap {
+    /// Maps texel index `i` into `0..size`.
+    fn apply(self, i: i64, size: i64) -> i64 {
+        match self {
+            Self::Repeat => i.rem_euclid(size),
+            Self::Clamp => i.clamp(0, size - 1),
+            Self::Mirror => {
+                let i = i.rem_euclid(2 * size);
+                if i < size { i } else { 2 * size - 1 - i }
+            }
+        }
+    }
+}
+
+/// An image stretched over the unit `(u, v)` square, `v = 0` at the bottom
+/// row.
+#[derive(Clone, Debug)]
+pub struct ImageTexture {
+    pub image: Arc<Image>,
+    pub filter: Filter,
+    pub wrap_u: Wrap,
+    pub wrap_v: Wrap,
+}
+
+impl ImageTexture {
+    /// Nearest-texel lookup, clamped at the edges.
+    #[inline]
+    #[must_use]
+    pub const fn new(image: Arc<Image>) -> Self {
+        Self { image, filter: Filter::Nearest, wrap_u: Wrap::Clamp, wrap_v: Wrap::Clamp }
+    }
+
+    /// Loads a PPM, PNG, PFM or HDR file, treating 8- and 16-bit samples as
+    /// sRGB.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        Ok(Self::new(Arc::new(Image::open(path, TransferFunction::Srgb)?)))
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_filter(self, filter: Filter) -> Self { Self { filter, ..self } }
+
+    /// Uses `wrap` along both axes.
+    #[inline]
+    #[must_use]
+    pub fn with_wrap(self, wrap: Wrap) -> Self { Self { wrap_u: wrap, wrap_v: wrap, ..self } }
+
+    /// The texel at integer coordinates, after wrapping.
+    fn texel(&self, x: i64, y: i64) -> Color3 {
+        let x = self.wrap_u.apply(x, i64::from(self.image.width()));
+        let y = self.wrap_v.apply(y, i64::from(self.image.height()));
+        let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) else { return Color3::CYAN };
+        self.image.get(x, y).unwrap_or(Color3::CYAN)
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: Point3) -> Color3 {
+        if self.image.pixels().is_empty() || !u.is_finite() || !v.is_finite() {
+            // Cyan makes a missing texture obvious in a render.
+            return Color3::CYAN;
+        }
+
+        // Continuous texel coordinates; image rows run top to bottom.
+        let x = u * f64::from(self.image.width());
+        let y = (1.0 - v) * f64::from(self.image.height());
+        match self.filter {
+            Filter::Nearest => self.texel(to_i64(x.floor()), to_i64(y.floor())),
+            Filter::Bilinear => {
+                // Texel centres sit at half-integer coordinates.
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (to_i64(x0), to_i64(y0));
+
+                let top = (1.0 - fx) * self.texel(x0, y0) + fx * self.texel(x0 + 1, y0);
+                let bottom = (1.0 - fx) * self.texel(x0, y0 + 1) + fx * self.texel(x0 + 1, y0 + 1);
+                (1.0 - fy) * top + fy * bottom
+            }
+        }
+    }
+}
+
+/// Saturating float-to-int conversion for texel coordinates.
+#[expect(clippy::as_conversions, clippy::cast_possible_truncation)]
+const fn to_i64(v: f64) -> i64 { v as i64 }
+
+// ---------------------------------------------------------------------------
+// Procedural noise textures
+// ---------------------------------------------------------------------------
+
+/// Grey Perlin noise with feature size `1 / scale`.
+#[derive(Clone, Debug)]
+pub struct NoiseTexture {
+    pub perlin: Perlin,
+    pub scale: f64,
+}
+
+impl NoiseTexture {
+    #[inline]
+    #[must_use]
+    pub const fn new(perlin: Perlin, scale: f64) -> Self { Self { perlin, scale } }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color3 {
+        // Map noise from [-1, 1] to [0, 1].
+        Color3::splat(0.5 * (1.0 + self.perlin.noise(self.scale * p)))
+    }
+}
+
+/// Marble veins: sine stripes along `z` whose phase is perturbed by
+/// turbulence.
+#[derive(Clone, Debug)]
+pub struct Marble {
+    pub perlin: Perlin,
+    /// Stripe frequency.
+    pub scale: f64,
+    /// How far turbulence bends the stripes.
+    pub distortion: f64,
+    pub octaves: u32,
+    pub color: Color3,
+}
+
+impl Marble {
+    /// The book's marble: white veins with distortion 10 over 7 octaves.
+    #[inline]
+    #[must_use]
+    pub const fn new(perlin: Perlin, scale: f64) -> Self {
+        Self { perlin, scale, distortion: 10.0, octaves: 7, color: Color3::WHITE }
+    }
+}
+
+impl Texture for Marble {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color3 {
+        let turbulence = self.perlin.turbulence(p, self.octaves);
+        let phase = self.scale * p.z + self.distortion * turbulence;
+        0.5 * (1.0 + phase.sin()) * self.color
+    }
+}
+
+/// Wood grain: concentric rings around the `y` axis, wobbled by
+/// turbulence.
+#[derive(Clone, Debug)]
+pub struct Wood {
+    pub perlin: Perlin,
+    /// Rings per unit distance from the axis.
+    pub scale: f64,
+    /// How far turbulence bends the rings, in rings.
+    pub distortion: f64,
+    pub octaves: u32,
+    pub light: Color3,
+    pub dark: Color3,
+}
+
+impl Wood {
+    #[inline]
+    #[must_use]
+    pub const fn new(perlin: Perlin, scale: f64) -> Self {
+        Self {
+            perlin,
+            scale,
+            distortion: 2.0,
+            octaves: 4,
+            light: Color3::new(0.79, 0.6, 0.4),
+            dark: Color3::new(0.45, 0.27, 0.13),
+        }
+    }
+}
+
+impl Texture for Wood {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color3 {
+        let radius = p.x.hypot(p.z);
+        let rings = self.scale * radius + self.distortion * self.perlin.turbulence(p, self.octaves);
+        // Sharpen each ring: mostly light wood with a thin dark band.
+        let t = rings.fract().powi(3);
+        (1.0 - t) * self.light + t * self.dark
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::point3;
+
+    #[test]
+    fn scenario_spatial_checker_alternates_per_cell() {
+        let checker = Checker::from_colors(1.0, Color3::WHITE, Color3::BLACK);
+        assert_eq!(checker.value(0.0, 0.0, point3(0.5, 0.5, 0.5)), Color3::WHITE);
+        assert_eq!(checker.value(0.0, 0.0, point3(1.5, 0.5, 0.5)), Color3::BLACK);
+        assert_eq!(checker.value(0.0, 0.0, point3(-0.5, 0.5, 0.5)), Color3::BLACK);
+        assert_eq!(checker.value(0.0, 0.0, point3(-0.5, -0.5, 0.5)), Color3::WHITE);
+    }
+
+    #[test]
+    fn scenario_uv_checker_ignores_position() {
+        let checker = UvChecker::from_colors(4.0, 2.0, Color3::RED, Color3::BLUE);
+        assert_eq!(checker.value(0.1, 0.1, Point3::ZERO), Color3::RED);
+        assert_eq!(checker.value(0.3, 0.1, point3(9.0, 9.0, 9.0)), Color3::BLUE);
+        assert_eq!(checker.value(0.3, 0.6, Point3::ZERO), Color3::RED);
+    }
+
+    #[test]
+    fn scenario_image_texture_v_runs_upward() {
+        let mut image = Image::new(2, 2);
+        image.set(0, 0, Color3::RED); // top-left
+        image.set(1, 1, Color3::GREEN); // bottom-right
+        let texture = ImageTexture::new(Arc::new(image));
+
+        assert_eq!(texture.value(0.25, 0.75, Point3::ZERO), Color3::RED);
+        assert_eq!(texture.value(0.75, 0.25, Point3::ZERO), Color3::GREEN);
+        assert_eq!(texture.value(1.0, 0.0, Point3::ZERO), Color3::GREEN);
+        assert_eq!(texture.value(-3.0, 7.0, Point3::ZERO), Color3::RED);
+    }
+
+    #[test]
+    fn scenario_wrap_modes() {
+        let mut image = Image::new(2, 1);
+        image.set(0, 0, Color3::RED);
+        image.set(1, 0, Color3::BLUE);
+        let texture = ImageTexture::new(Arc::new(image));
+
+        // u = 1.25 lands in the first texel of the next tile.
+        let at = |t: &ImageTexture| t.value(1.25, 0.5, Point3::ZERO);
+        assert_eq!(at(&texture), Color3::BLUE);
+        assert_eq!(at(&texture.clone().with_wrap(Wrap::Repeat)), Color3::RED);
+        assert_eq!(at(&texture.clone().with_wrap(Wrap::Mirror)), Color3::BLUE);
+        assert_eq!(texture.with_wrap(Wrap::Mirror).value(1.75, 0.5, Point3::ZERO), Color3::RED);
+    }
+
+    #[test]
+    fn scenario_bilinear_blends_between_texel_centres() {
+        let mut image = Image::new(2, 1);
+        image.set(0, 0, Color3::BLACK);
+        image.set(1, 0, Color3::WHITE);
+        let texture = ImageTexture::new(Arc::new(image)).with_filter(Filter::Bilinear);
+
+        assert_eq!(texture.value(0.25, 0.5, Point3::ZERO), Color3::BLACK);
+        assert_eq!(texture.value(0.5, 0.5, Point3::ZERO), Color3::splat(0.5));
+        assert_eq!(texture.value(0.625, 0.5, Point3::ZERO), Color3::splat(0.75));
+        assert_eq!(texture.value(1.0, 0.5, Point3::ZERO), Color3::WHITE);
+    }
+
+    #[test]
+    fn scenario_missing_file_is_a_typed_error() {
+        let err = ImageTexture::open("/definitely/not/here.png").unwrap_err();
+        assert!(matches!(err, ImageError::NotFound(_)), "{err}");
+
+        let err = ImageTexture::open("texture.tga").unwrap_err();
+        assert!(matches!(err, ImageError::UnsupportedFormat(_)), "{err}");
+    }
+}
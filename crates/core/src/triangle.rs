@@ -0,0 +1,342 @@
+use std::sync::Arc;
+
+use rand::prelude::Rng;
+use shared::random;
+
+use crate::prelude::{
+    AABB,
+    Color3,
+    HitRecord,
+    Hittable,
+    Interval,
+    Material,
+    Point3,
+    Ray,
+    Vec3,
+    interval,
+    point3,
+};
+
+/// Determinants smaller than this mean the ray runs along the triangle's
+/// plane (or the triangle has no area).
+const PARALLEL_EPSILON: f64 = 1e-12;
+
+/// How far outside `[0, 1]` a barycentric coordinate may stray and still
+/// count as a hit. Neighbours sharing an edge overlap by this much instead
+/// of leaving a crack that rays can slip through.
+const EDGE_EPSILON: f64 = 1e-9;
+
+/// Which sides of a triangle can be hit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Culling {
+    /// Both sides are hit.
+    #[default]
+    DoubleSided,
+    /// Only the front side — where `(b − a) × (c − a)` points — is hit.
+    BackFace,
+}
+
+/// A triangle with vertices `a`, `b` and `c`, in counter-clockwise order
+/// seen from the front.
+///
+/// Per-vertex normals, texture coordinates and colours are optional; when
+/// present they are blended with the hit's barycentric coordinates.
+#[derive(Clone)]
+pub struct Triangle {
+    pub vertices: [Point3; 3],
+    /// Shading normals at each vertex. The geometric normal is used when
+    /// `None`.
+    pub normals: Option<[Vec3; 3]>,
+    /// `(u, v)` at each vertex. Defaults to `(0, 0)`, `(1, 0)`, `(0, 1)`, so
+    /// `u` and `v` are the barycentric weights of `b` and `c`.
+    pub uvs: Option<[(f64, f64); 3]>,
+    /// Colours at each vertex, reported as [`HitRecord::color`].
+    pub colors: Option<[Color3; 3]>,
+    pub material: Arc<dyn Material>,
+    pub culling: Culling,
+    bbox: AABB,
+}
+
+impl Triangle {
+    #[must_use]
+    pub fn new(a: Point3, b: Point3, c: Point3, material: Arc<dyn Material>) -> Self {
+        let bbox = bounds([a, b, c]);
+        Self {
+            vertices: [a, b, c],
+            normals: None,
+            uvs: None,
+            colors: None,
+            material,
+            culling: Culling::DoubleSided,
+            bbox,
+        }
+    }
+
+    #[must_use]
+    pub fn with_normals(self, normals: [Vec3; 3]) -> Self {
+        Self { normals: Some(normals), ..self }
+    }
+
+    #[must_use]
+    pub fn with_uvs(self, uvs: [(f64, f64); 3]) -> Self { Self { uvs: Some(uvs), ..self } }
+
+    #[must_use]
+    pub fn with_colors(self, colors: [Color3; 3]) -> Self { Self { colors: Some(colors), ..self } }
+
+    #[must_use]
+    pub fn with_culling(self, culling: Culling) -> Self { Self { culling, ..self } }
+
+    /// Unit normal of the triangle's plane, on the front side.
+    #[must_use]
+    pub fn geometric_normal(&self) -> Vec3 {
+        let [a, b, c] = self.vertices;
+        (b - a).cross(c - a).unit()
+    }
+}
+
+impl Hittable for Triangle {
+    fn bounding_box(&self) -> AABB { self.bbox }
+
+    fn hit(&self, ray: &Ray, t: Interval) -> Option<HitRecord> {
+        let attributes = Attributes { normals: self.normals, uvs: self.uvs, colors: self.colors };
+        hit_triangle(self.vertices, attributes, &self.material, self.culling, ray, t)
+    }
+
+    fn is_sampleable(&self) -> bool { true }
+
+    /// Points are sampled uniformly by area, so the density per solid angle
+    /// is distance² / (cos θ × area).
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> Option<f64> {
+        let ray = Ray::new(origin, direction, None);
+        let Some(rec) = self.hit(&ray, interval(0.001, f64::INFINITY)) else {
+            return Some(0.0);
+        };
+        let [a, b, c] = self.vertices;
+        let n = (b - a).cross(c - a);
+        let distance_squared = rec.t * rec.t * direction.length_squared();
+        let cosine = direction.dot(n).abs() / (direction.length() * n.length());
+        Some(distance_squared / (cosine * area(self.vertices)))
+    }
+
+    fn random(&self, rng: &mut dyn Rng, origin: Point3) -> Option<Vec3> {
+        Some(sample_point(rng, self.vertices) - origin)
+    }
+}
+
+/// Area of the triangle with corners `vertices`.
+pub(crate) fn area([a, b, c]: [Point3; 3]) -> f64 { 0.5 * (b - a).cross(c - a).length() }
+
+/// A point drawn uniformly from the triangle with corners `vertices`.
+pub(crate) fn sample_point(rng: &mut dyn Rng, [a, b, c]: [Point3; 3]) -> Point3 {
+    // Folding the unit square onto the triangle keeps the samples uniform.
+    let (r1, r2) = (random(rng), random(rng));
+    let (r1, r2) = if r1 + r2 > 1.0 { (1.0 - r1, 1.0 - r2) } else { (r1, r2) };
+    a + r1 * (b - a) + r2 * (c - a)
+}
+
+/// Padded box around a triangle's corners. Padding the corners' box, rather
+/// than joining per-edge boxes, keeps the padding to the flat axis of an
+/// axis-aligned triangle.
+pub(crate) fn bounds([a, b, c]: [Point3; 3]) -> AABB {
+    let min = point3(a.x.min(b.x).min(c.x), a.y.min(b.y).min(c.y), a.z.min(b.z).min(c.z));
+    let max = point3(a.x.max(b.x).max(c.x), a.y.max(b.y).max(c.y), a.z.max(b.z).max(c.z));
+    AABB::from((min, max))
+}
+
+/// Optional per-vertex data, blended across a hit with its barycentric
+/// weights.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Attributes {
+    pub normals: Option<[Vec3; 3]>,
+    pub uvs: Option<[(f64, f64); 3]>,
+    pub colors: Option<[Color3; 3]>,
+}
+
+/// Intersects one triangle and fills in its hit record. Shared by
+/// [`Triangle`] and [`TriangleMesh`](crate::mesh::TriangleMesh), which keeps
+/// its vertex attributes in buffers instead.
+pub(crate) fn hit_triangle(
+    vertices: [Point3; 3],
+    attributes: Attributes,
+    material: &Arc<dyn Material>,
+    culling: Culling,
+    ray: &Ray,
+    t: Interval,
+) -> Option<HitRecord> {
+    let (t_hit, u, v) = intersect(vertices, ray, culling)?;
+    if !t.contains(t_hit) {
+        return None;
+    }
+    let w = 1.0 - u - v;
+
+    let Attributes { normals, uvs, colors } = attributes;
+    let (tex_u, tex_v) = uvs.map_or((u, v), |[uv0, uv1, uv2]| {
+        (w * uv0.0 + u * uv1.0 + v * uv2.0, w * uv0.1 + u * uv1.1 + v * uv2.1)
+    });
+
+    let [a, b, c] = vertices;
+    let geometric = (b - a).cross(c - a).unit();
+    let mut record = HitRecord {
+        p: ray.at(t_hit),
+        normal: geometric, // overwritten below
+        t: t_hit,
+        u: tex_u,
+        v: tex_v,
+        color: colors.map(|[c0, c1, c2]| w * c0 + u * c1 + v * c2),
+        material: Arc::clone(material),
+        is_front_face: false, // overwritten below
+    };
+    record.set_face_normal(ray, geometric);
+
+    if let Some([n0, n1, n2]) = normals {
+        let shading = (w * n0 + u * n1 + v * n2).unit();
+        if shading.x.is_finite() {
+            // Keep the shading normal on the same side as the one
+            // `set_face_normal` chose, whatever the vertex normals' winding.
+            let facing = if shading.dot(geometric) < 0.0 { -shading } else { shading };
+            record.normal = if record.is_front_face { facing } else { -facing };
+        }
+    }
+    Some(record)
+}
+
+/// Möller–Trumbore ray–triangle intersection.
+///
+/// Returns the ray parameter and the barycentric weights `(u, v)` of the
+/// second and third vertices, clamped so `u`, `v` and `1 − u − v` all lie in
+/// `[0, 1]`. Edge tests are padded by [`EDGE_EPSILON`] so a ray through an
+/// edge shared by two triangles hits at least one of them.
+fn intersect([a, b, c]: [Point3; 3], ray: &Ray, culling: Culling) -> Option<(f64, f64, f64)> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = ray.direction.cross(edge2);
+    let det = edge1.dot(p);
+
+    let parallel = match culling {
+        Culling::DoubleSided => det.abs() < PARALLEL_EPSILON,
+        Culling::BackFace => det < PARALLEL_EPSILON,
+    };
+    if parallel {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let s = ray.origin - a;
+    let u = inv_det * s.dot(p);
+    if !(-EDGE_EPSILON..=1.0 + EDGE_EPSILON).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(edge1);
+    let v = inv_det * ray.direction.dot(q);
+    if v < -EDGE_EPSILON || u + v > 1.0 + EDGE_EPSILON {
+        return None;
+    }
+
+    let t = inv_det * edge2.dot(q);
+    let u = u.clamp(0.0, 1.0);
+    let v = v.clamp(0.0, 1.0 - u);
+    Some((t, u, v))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng as _;
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::prelude::{Hittables, Lambertian, color, vec3};
+
+    const ANY: Interval = Interval::new(0.001, f64::INFINITY);
+
+    fn material() -> Arc<dyn Material> { Arc::new(Lambertian::new(color(0.5, 0.5, 0.5))) }
+
+    /// The lower-left half of the unit square in z = 0, facing +z.
+    fn triangle() -> Triangle { Triangle::new(Point3::ZERO, Vec3::X, Vec3::Y, material()) }
+
+    fn down_at(x: f64, y: f64) -> Ray { Ray::new(point3(x, y, 2.0), Vec3::NEG_Z, None) }
+
+    #[test]
+    fn scenario_hit_reports_barycentric_uv() {
+        let rec = triangle().hit(&down_at(0.25, 0.5), ANY).unwrap();
+        shared::assert_fuzzy_eq!(rec.t, 2.0);
+        shared::assert_fuzzy_eq!(rec.u, 0.25);
+        shared::assert_fuzzy_eq!(rec.v, 0.5);
+        assert_eq!(rec.normal, Vec3::Z);
+        assert!(rec.is_front_face);
+
+        assert!(triangle().hit(&down_at(0.75, 0.5), ANY).is_none());
+        assert!(triangle().hit(&down_at(-0.1, 0.5), ANY).is_none());
+        assert!(triangle().hit(&Ray::new(point3(0.1, 0.1, 0.0), Vec3::X, None), ANY).is_none());
+        assert!(triangle().hit(&down_at(0.25, 0.25), Interval::new(0.0, 1.0)).is_none());
+    }
+
+    #[test]
+    fn scenario_vertex_attributes_are_interpolated() {
+        let tri = triangle().with_uvs([(0.0, 1.0), (1.0, 1.0), (0.0, 0.0)]).with_normals([
+            Vec3::Z,
+            Vec3::Z,
+            vec3(1.0, 0.0, 1.0).unit(),
+        ]);
+
+        // At vertex c both attributes come straight from the third entry.
+        let rec = tri.hit(&down_at(0.0, 1.0), ANY).unwrap();
+        shared::assert_fuzzy_eq!(rec.u, 0.0);
+        shared::assert_fuzzy_eq!(rec.v, 0.0);
+        shared::assert_fuzzy_eq!(rec.normal.x, vec3(1.0, 0.0, 1.0).unit().x);
+
+        // Halfway between a and c.
+        let rec = tri.hit(&down_at(0.0, 0.5), ANY).unwrap();
+        shared::assert_fuzzy_eq!(rec.v, 0.5);
+        shared::assert_fuzzy_eq!(rec.normal.length(), 1.0);
+        assert!(rec.normal.x > 0.0 && rec.normal.z > rec.normal.x);
+
+        // From behind, the shading normal flips with the face.
+        let rec = tri.hit(&Ray::new(point3(0.0, 0.5, -1.0), Vec3::Z, None), ANY).unwrap();
+        assert!(!rec.is_front_face);
+        assert!(rec.normal.z < 0.0);
+    }
+
+    #[test]
+    fn scenario_back_face_culling() {
+        let from_behind = Ray::new(point3(0.25, 0.25, -1.0), Vec3::Z, None);
+        let rec = triangle().hit(&from_behind, ANY).unwrap();
+        assert_eq!(rec.normal, Vec3::NEG_Z);
+        assert!(!rec.is_front_face);
+
+        let culled = triangle().with_culling(Culling::BackFace);
+        assert!(culled.hit(&from_behind, ANY).is_none());
+        assert!(culled.hit(&down_at(0.25, 0.25), ANY).is_some());
+    }
+
+    #[test]
+    fn scenario_shared_edges_do_not_leak() {
+        // Two triangles splitting a tilted quad along its diagonal.
+        let (a, b) = (point3(-1.0, -1.0, 0.3), point3(1.0, -1.0, -0.2));
+        let (c, d) = (point3(1.0, 1.0, 0.1), point3(-1.0, 1.0, -0.4));
+        let mesh = Hittables::from(vec![
+            Triangle::new(a, b, c, material()),
+            Triangle::new(a, c, d, material()),
+        ]);
+
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..10_000 {
+            let target = a + random(&mut rng) * (c - a);
+            let origin = point3(0.3, -0.2, 4.0) + Vec3::random_range(&mut rng, -2.0, 2.0);
+            let ray = Ray::new(origin, target - origin, None);
+            assert!(mesh.hit(&ray, ANY).is_some(), "ray towards {target} leaked");
+        }
+    }
+
+    #[test]
+    fn scenario_flat_bounding_box_is_padded() {
+        let bbox = triangle().bounding_box();
+        assert!(bbox.z.size() > 0.0);
+        assert_eq!((bbox.x.min, bbox.x.max, bbox.y.max), (0.0, 1.0, 1.0));
+        assert!(bbox.hit(&down_at(0.25, 0.25), ANY));
+    }
+}
@@ -21,6 +21,16 @@ pub fn random_range(rng: &mut dyn Rng, min: f64, max: f64) -> f64 {
     min + (max - min) * rng.random::<f64>()
 }
 
+/// Returns a random index in *`[0,len)`*, e.g. for shuffling.
+///
+/// `len` must be non-zero.
+#[inline]
+#[must_use]
+pub fn random_index(rng: &mut dyn Rng, len: usize) -> usize {
+    debug_assert!(len > 0, "random_index: len must be > 0");
+    rng.random_range(0..len)
+}
+
 pub trait FuzzyEq<Rhs = Self>
 where
     Self: Sized,